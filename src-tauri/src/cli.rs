@@ -0,0 +1,225 @@
+use std::{env, fs::remove_dir_all, path::PathBuf, sync::Mutex};
+
+use serde_json::Value;
+use tauri::{
+    ipc::{Channel, InvokeResponseBody},
+    Listener, Manager,
+};
+use tauri_plugin_cli::{Matches, SubcommandMatches};
+use tokio::sync::mpsc;
+
+use crate::{
+    client::{ClientMatcher, ClientProvider, DEFAULT_CLIENT},
+    process::kill_process,
+    scripts::{launch_script, new_script_id, unix_now, LaunchRequest},
+    settings::SettingsStore,
+    simba::sync_plugins_repo,
+    LauncherVariables,
+};
+
+fn arg(matches: &Matches, name: &str) -> Option<String> {
+    match matches.args.get(name).map(|arg| &arg.value) {
+        Some(Value::String(value)) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+fn flag(matches: &Matches, name: &str) -> bool {
+    matches
+        .args
+        .get(name)
+        .map(|arg| arg.occurrences > 0)
+        .unwrap_or(false)
+}
+
+// Release builds use the windows subsystem and start without a console, so borrow the
+// one of the shell we were started from.
+#[cfg(target_os = "windows")]
+fn attach_console() {
+    use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    let _ = unsafe { AttachConsole(ATTACH_PARENT_PROCESS) };
+}
+
+#[cfg(not(target_os = "windows"))]
+fn attach_console() {}
+
+/// Runs a CLI subcommand without a window and exits the app with its status code.
+pub fn run_headless(app: tauri::AppHandle, command: SubcommandMatches) {
+    attach_console();
+
+    tauri::async_runtime::spawn(async move {
+        let result = match command.name.as_str() {
+            "run" => run(&app, &command.matches).await,
+            "list" => list(&app),
+            "stop" => stop(&app, &command.matches),
+            "sync-plugins" => sync_plugins(&app).await,
+            "clean" => clean(&app, &command.matches),
+            other => Err(format!("Unknown command: {}", other)),
+        };
+
+        match result {
+            Ok(code) => app.exit(code),
+            Err(e) => {
+                eprintln!("{}", e);
+                app.exit(1);
+            }
+        }
+    });
+}
+
+fn simba_path(app: &tauri::AppHandle, dev: bool) -> PathBuf {
+    let launcher = app.state::<Mutex<LauncherVariables>>();
+    let launcher = launcher.lock().unwrap();
+    if dev {
+        launcher.devsimba.clone()
+    } else {
+        launcher.simba.clone()
+    }
+}
+
+async fn run(app: &tauri::AppHandle, matches: &Matches) -> Result<i32, String> {
+    let script = arg(matches, "script").ok_or("Missing script to run")?;
+    let args = vec![
+        script,
+        arg(matches, "simba").unwrap_or_else(|| "latest".to_string()),
+        arg(matches, "wasplib").unwrap_or_else(|| "latest".to_string()),
+        arg(matches, "id").unwrap_or_default(),
+        arg(matches, "revision").unwrap_or_default(),
+        env::var("WASP_REFRESH_TOKEN").unwrap_or_default(),
+    ];
+
+    let rules = app.state::<SettingsStore>().get().client_rules;
+    let provider = app.state::<Box<dyn ClientProvider>>();
+    let clients = provider.list_clients(&ClientMatcher::new(&rules)?)?;
+    let client = match arg(matches, "client") {
+        Some(pid) => {
+            let pid: u32 = pid.parse().map_err(|_| format!("Invalid PID: {}", pid))?;
+            clients.into_iter().find(|c| c.pid() == pid)
+        }
+        None => clients.into_iter().next(),
+    }
+    .ok_or("No game client found")?;
+
+    {
+        let launcher = app.state::<Mutex<LauncherVariables>>();
+        let mut launcher = launcher.lock().unwrap();
+        launcher.clients.insert(DEFAULT_CLIENT.to_string(), client);
+    }
+
+    // Forward the script output to our own stdout instead of a webview.
+    let channel: Channel<String> = Channel::new(|body| {
+        if let InvokeResponseBody::Json(json) = body {
+            if let Ok(line) = serde_json::from_str::<String>(&json) {
+                println!("{}", line);
+            }
+        }
+        Ok(())
+    });
+    let id = new_script_id(app);
+
+    // Listen before launching, a script that fails right away may exit before
+    // `launch_script` returns.
+    let (exit_tx, mut exit_rx) = mpsc::unbounded_channel();
+    let listener = app.listen("script-exit", move |event| {
+        if let Ok(exit) = serde_json::from_str::<Value>(event.payload()) {
+            if exit["id"] == id {
+                let _ = exit_tx.send(exit["code"].as_i64());
+            }
+        }
+    });
+
+    let launched = launch_script(
+        app,
+        LaunchRequest {
+            id,
+            args,
+            client: None,
+            profile: arg(matches, "profile"),
+            force: flag(matches, "force"),
+            channel,
+            restarts: 0,
+        },
+    )
+    .await;
+    if let Err(e) = launched {
+        app.unlisten(listener);
+        return Err(e);
+    }
+    println!("Started script {}", id);
+
+    let code = exit_rx.recv().await.flatten();
+    app.unlisten(listener);
+
+    match code {
+        Some(code) => println!("Script {} exited with code {}", id, code),
+        None => println!("Script {} was stopped", id),
+    }
+    Ok(code.map(|code| code as i32).unwrap_or(1))
+}
+
+fn list(app: &tauri::AppHandle) -> Result<i32, String> {
+    let launcher = app.state::<Mutex<LauncherVariables>>();
+    let launcher = launcher.lock().unwrap();
+    let records = launcher.registry.lock().unwrap().prune();
+
+    let now = unix_now();
+    for record in records {
+        println!(
+            "{}\tPID {}\t{}\t{}s",
+            record.id,
+            record.pid,
+            record.script_id,
+            now.saturating_sub(record.started)
+        );
+    }
+
+    Ok(0)
+}
+
+fn stop(app: &tauri::AppHandle, matches: &Matches) -> Result<i32, String> {
+    let id = arg(matches, "id").ok_or("Missing script id")?;
+    let id: u32 = id
+        .parse()
+        .map_err(|_| format!("Invalid script id: {}", id))?;
+
+    let launcher = app.state::<Mutex<LauncherVariables>>();
+    let launcher = launcher.lock().unwrap();
+    let mut registry = launcher.registry.lock().unwrap();
+
+    let Some(record) = registry.prune().into_iter().find(|r| r.id == id) else {
+        return Err(format!("No active script found for ID {}", id));
+    };
+
+    kill_process(record.pid).map_err(|e| format!("Failed to kill: {}", e))?;
+    // The instance watching the script records its run once it notices the exit.
+    if !record.is_owned() {
+        registry.remove(id);
+    }
+    println!("Process {} killed", id);
+    Ok(0)
+}
+
+async fn sync_plugins(app: &tauri::AppHandle) -> Result<i32, String> {
+    let plugins_path = simba_path(app, false).join("Plugins").join("wasp-plugins");
+    sync_plugins_repo(&plugins_path)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(0)
+}
+
+fn clean(app: &tauri::AppHandle, matches: &Matches) -> Result<i32, String> {
+    let path = simba_path(app, flag(matches, "dev"));
+    let target = match arg(matches, "target").as_deref() {
+        Some("cache") => path.join("Data").join("Cache"),
+        Some("assets") => path.join("Data").join("Assets"),
+        Some("configs") => path.join("Configs"),
+        _ => return Err("Expected one of: cache, assets, configs".to_string()),
+    };
+
+    if target.exists() {
+        remove_dir_all(&target).map_err(|e| e.to_string())?;
+        println!("Deleted folder: {:?}", target);
+    }
+
+    Ok(0)
+}
@@ -0,0 +1,36 @@
+/// Pixels of a client window, top row first, 4 bytes per pixel in BGRA order.
+pub struct Capture {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Capture {
+    /// Encodes the capture as an uncompressed 32-bit BMP, which needs no image crate.
+    pub fn to_bmp(&self) -> Vec<u8> {
+        const HEADER_SIZE: u32 = 14 + 40;
+        let size = HEADER_SIZE + self.pixels.len() as u32;
+
+        let mut bmp = Vec::with_capacity(size as usize);
+        bmp.extend_from_slice(b"BM");
+        bmp.extend_from_slice(&size.to_le_bytes());
+        bmp.extend_from_slice(&0u32.to_le_bytes());
+        bmp.extend_from_slice(&HEADER_SIZE.to_le_bytes());
+
+        bmp.extend_from_slice(&40u32.to_le_bytes());
+        bmp.extend_from_slice(&(self.width as i32).to_le_bytes());
+        // A negative height stores the rows top-down.
+        bmp.extend_from_slice(&(-(self.height as i32)).to_le_bytes());
+        bmp.extend_from_slice(&1u16.to_le_bytes());
+        bmp.extend_from_slice(&32u16.to_le_bytes());
+        bmp.extend_from_slice(&0u32.to_le_bytes());
+        bmp.extend_from_slice(&(self.pixels.len() as u32).to_le_bytes());
+        bmp.extend_from_slice(&2835i32.to_le_bytes());
+        bmp.extend_from_slice(&2835i32.to_le_bytes());
+        bmp.extend_from_slice(&0u32.to_le_bytes());
+        bmp.extend_from_slice(&0u32.to_le_bytes());
+
+        bmp.extend_from_slice(&self.pixels);
+        bmp
+    }
+}
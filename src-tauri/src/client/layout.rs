@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::WindowGeometry;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WindowLayout {
+    /// Windows of `width` x `height` laid out left to right, `columns` per row.
+    Grid {
+        columns: u32,
+        width: u32,
+        height: u32,
+        #[serde(default)]
+        x: i32,
+        #[serde(default)]
+        y: i32,
+    },
+    /// Windows of `width` x `height`, each shifted by `offset` from the previous one.
+    Cascade {
+        offset: i32,
+        width: u32,
+        height: u32,
+        #[serde(default)]
+        x: i32,
+        #[serde(default)]
+        y: i32,
+    },
+    /// Explicit rectangles keyed by client slot.
+    Custom {
+        windows: HashMap<String, WindowGeometry>,
+    },
+}
+
+impl WindowLayout {
+    /// Assigns a rectangle to each slot. Grid and cascade go through the slots in
+    /// name order so the result is stable between runs.
+    pub fn arrange(&self, slots: &[String]) -> Vec<(String, WindowGeometry)> {
+        let mut slots = slots.to_vec();
+        slots.sort();
+
+        match self {
+            WindowLayout::Grid {
+                columns,
+                width,
+                height,
+                x,
+                y,
+            } => {
+                let columns = (*columns).max(1) as usize;
+                slots
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, slot)| {
+                        let column = (i % columns) as i32;
+                        let row = (i / columns) as i32;
+                        let geometry = WindowGeometry {
+                            x: x + column * *width as i32,
+                            y: y + row * *height as i32,
+                            width: *width,
+                            height: *height,
+                        };
+                        (slot, geometry)
+                    })
+                    .collect()
+            }
+            WindowLayout::Cascade {
+                offset,
+                width,
+                height,
+                x,
+                y,
+            } => slots
+                .into_iter()
+                .enumerate()
+                .map(|(i, slot)| {
+                    let geometry = WindowGeometry {
+                        x: x + offset * i as i32,
+                        y: y + offset * i as i32,
+                        width: *width,
+                        height: *height,
+                    };
+                    (slot, geometry)
+                })
+                .collect(),
+            WindowLayout::Custom { windows } => slots
+                .into_iter()
+                .filter_map(|slot| {
+                    let geometry = *windows.get(&slot)?;
+                    Some((slot, geometry))
+                })
+                .collect(),
+        }
+    }
+}
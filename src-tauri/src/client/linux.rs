@@ -1,12 +1,564 @@
-pub fn list_processes() {
-    if let Ok(entries) = fs::read_dir("/proc") {
-        for entry in entries.flatten() {
-            let s_name = entry.file_name().to_string_lossy().into_owned();
-            if s_name.chars().all(|c| c.is_numeric()) {
-                if let Ok(comm) = fs::read_to_string(format!("/proc/{}/comm", s_name)) {
-                    println!("PID: {} | Name: {}", s_name, comm.trim());
-                }
+use std::{path::Path, sync::Mutex};
+
+use x11rb::{
+    connection::Connection,
+    protocol::xproto::{
+        AtomEnum, ClientMessageEvent, ConfigureWindowAux, ConnectionExt, EventMask, ImageFormat,
+        Window,
+    },
+    rust_connection::RustConnection,
+};
+
+use crate::process::Procfs;
+
+use super::{Capture, ClientMatcher, ClientProvider, WindowGeometry, WindowMatch};
+
+const CLIENT_KEYWORDS: [&str; 3] = ["runelite", "jagex", "oldschool"];
+
+#[derive(Debug, Clone)]
+pub struct ClientProcess {
+    pub pid: u32,
+    pub name: String,
+    pub cmdline: Vec<String>,
+}
+
+fn contains_keyword(value: &str) -> bool {
+    let value = value.to_lowercase();
+    CLIENT_KEYWORDS.iter().any(|k| value.contains(k))
+}
+
+fn is_client_process(procfs: &Procfs, pid: u32, name: &str) -> bool {
+    if contains_keyword(name) {
+        return true;
+    }
+
+    let cmdline = procfs.cmdline(pid);
+    let is_java = name.to_lowercase().starts_with("java")
+        || cmdline
+            .first()
+            .and_then(|arg| Path::new(arg).file_name())
+            .is_some_and(|exe| exe.to_string_lossy().to_lowercase().starts_with("java"));
+
+    if is_java && cmdline.iter().any(|arg| contains_keyword(arg)) {
+        return true;
+    }
+
+    // RuneLite's AppImage re-executes its bundled JRE, so only the environment remembers it.
+    procfs
+        .environ(pid)
+        .iter()
+        .any(|(key, value)| key == "APPIMAGE" && contains_keyword(value))
+}
+
+/// Finds game client processes under `procfs`, either Java clients or whatever
+/// matches the user's process name patterns.
+pub fn find_client_processes(procfs: &Procfs, matcher: &ClientMatcher) -> Vec<ClientProcess> {
+    procfs
+        .pids()
+        .into_iter()
+        .filter_map(|pid| {
+            let name = procfs
+                .exe(pid)
+                .and_then(|exe| exe.file_name().map(|n| n.to_string_lossy().into_owned()))
+                .or_else(|| procfs.comm(pid))?;
+
+            let is_client = if matcher.has_process_patterns() {
+                matcher.matches_process(&name)
+            } else {
+                is_client_process(procfs, pid, &name)
+            };
+
+            if is_client {
+                Some(ClientProcess {
+                    pid,
+                    name,
+                    cmdline: procfs.cmdline(pid),
+                })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+struct X11 {
+    conn: RustConnection,
+    root: Window,
+}
+
+impl X11 {
+    fn connect() -> Result<Self, String> {
+        let (conn, screen) = x11rb::connect(None).map_err(|e| e.to_string())?;
+        let root = conn.setup().roots[screen].root;
+        Ok(Self { conn, root })
+    }
+
+    /// Whether the server still answers, checked with a cheap round trip.
+    fn is_connected(&self) -> bool {
+        self.conn
+            .get_input_focus()
+            .is_ok_and(|cookie| cookie.reply().is_ok())
+    }
+
+    fn atom(&self, name: &str) -> Result<u32, String> {
+        let reply = self
+            .conn
+            .intern_atom(false, name.as_bytes())
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map_err(|e| e.to_string())?;
+        Ok(reply.atom)
+    }
+
+    fn client_list(&self) -> Result<Vec<Window>, String> {
+        let atom = self.atom("_NET_CLIENT_LIST")?;
+        let reply = self
+            .conn
+            .get_property(false, self.root, atom, AtomEnum::WINDOW, 0, u32::MAX)
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map_err(|e| e.to_string())?;
+        Ok(reply.value32().map(|v| v.collect()).unwrap_or_default())
+    }
+
+    fn window_pid(&self, window: Window, atom: u32) -> Option<u32> {
+        let reply = self
+            .conn
+            .get_property(false, window, atom, AtomEnum::CARDINAL, 0, 1)
+            .ok()?
+            .reply()
+            .ok()?;
+        let pid = reply.value32()?.next();
+        pid
+    }
+
+    fn window_class(&self, window: Window) -> Vec<String> {
+        let Ok(cookie) =
+            self.conn
+                .get_property(false, window, AtomEnum::WM_CLASS, AtomEnum::STRING, 0, 1024)
+        else {
+            return Vec::new();
+        };
+        let Ok(reply) = cookie.reply() else {
+            return Vec::new();
+        };
+
+        // WM_CLASS holds the instance and class names as two NUL terminated strings.
+        reply
+            .value
+            .split(|b| *b == 0)
+            .filter(|part| !part.is_empty())
+            .map(|part| String::from_utf8_lossy(part).into_owned())
+            .collect()
+    }
+
+    fn window_title(&self, window: Window) -> String {
+        let utf8 = self.atom("UTF8_STRING").ok();
+        let net_wm_name = self.atom("_NET_WM_NAME").ok();
+
+        let read = |property: u32, kind: u32| -> Option<String> {
+            let reply = self
+                .conn
+                .get_property(false, window, property, kind, 0, 1024)
+                .ok()?
+                .reply()
+                .ok()?;
+            if reply.value.is_empty() {
+                return None;
             }
+            Some(String::from_utf8_lossy(&reply.value).into_owned())
+        };
+
+        net_wm_name
+            .zip(utf8)
+            .and_then(|(name, utf8)| read(name, utf8))
+            .or_else(|| read(AtomEnum::WM_NAME.into(), AtomEnum::STRING.into()))
+            .unwrap_or_default()
+    }
+
+    fn window_geometry(&self, window: Window) -> Option<WindowGeometry> {
+        let geometry = self.conn.get_geometry(window).ok()?.reply().ok()?;
+        let origin = self
+            .conn
+            .translate_coordinates(window, self.root, 0, 0)
+            .ok()?
+            .reply()
+            .ok()?;
+
+        Some(WindowGeometry {
+            x: origin.dst_x as i32,
+            y: origin.dst_y as i32,
+            width: geometry.width as u32,
+            height: geometry.height as u32,
+        })
+    }
+
+    /// Size of the decorations around `window` as left, right, top and bottom.
+    fn frame_extents(&self, window: Window) -> Option<[u32; 4]> {
+        // Set by EWMH window managers, including ones that don't reparent.
+        let atom = self.atom("_NET_FRAME_EXTENTS").ok()?;
+        let reply = self
+            .conn
+            .get_property(false, window, atom, AtomEnum::CARDINAL, 0, 4)
+            .ok()?
+            .reply()
+            .ok()?;
+        if let Some(values) = reply.value32() {
+            if let Ok(extents) = <[u32; 4]>::try_from(values.collect::<Vec<_>>()) {
+                return Some(extents);
+            }
+        }
+
+        // Otherwise a reparenting window manager's frame is the ancestor right below the root.
+        let mut frame = window;
+        loop {
+            let tree = self.conn.query_tree(frame).ok()?.reply().ok()?;
+            if tree.parent == tree.root || tree.parent == x11rb::NONE {
+                break;
+            }
+            frame = tree.parent;
+        }
+
+        let inner = self.window_geometry(window)?;
+        let outer = self.window_geometry(frame)?;
+        let left = (inner.x - outer.x).max(0) as u32;
+        let top = (inner.y - outer.y).max(0) as u32;
+        Some([
+            left,
+            outer.width.saturating_sub(inner.width + left),
+            top,
+            outer.height.saturating_sub(inner.height + top),
+        ])
+    }
+
+    fn frame_geometry(&self, window: Window) -> Option<WindowGeometry> {
+        let inner = self.window_geometry(window)?;
+        let [left, right, top, bottom] = self.frame_extents(window)?;
+        Some(WindowGeometry {
+            x: inner.x - left as i32,
+            y: inner.y - top as i32,
+            width: inner.width + left + right,
+            height: inner.height + top + bottom,
+        })
+    }
+
+    /// Moves and resizes the frame of `window` to `geometry`.
+    fn move_frame(&self, window: Window, geometry: WindowGeometry) -> bool {
+        let [left, right, top, bottom] = self.frame_extents(window).unwrap_or_default();
+        let (x, y) = (geometry.x + left as i32, geometry.y + top as i32);
+        let width = geometry.width.saturating_sub(left + right).max(1);
+        let height = geometry.height.saturating_sub(top + bottom).max(1);
+
+        let sent = match self.atom("_NET_MOVERESIZE_WINDOW") {
+            Ok(atom) if left + right + top + bottom > 0 => {
+                // Static gravity places the window itself rather than its frame at x, y.
+                // Bits 8 to 11 mark all four values as set, source 2 is a pager or tool.
+                let flags = 10 | 0xf << 8 | 2 << 12;
+                let event = ClientMessageEvent::new(
+                    32,
+                    window,
+                    atom,
+                    [flags, x as u32, y as u32, width, height],
+                );
+                self.conn
+                    .send_event(
+                        false,
+                        self.root,
+                        EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+                        event,
+                    )
+                    .is_ok()
+            }
+            // Undecorated, or no window manager to ask.
+            _ => {
+                let aux = ConfigureWindowAux::new()
+                    .x(x)
+                    .y(y)
+                    .width(width)
+                    .height(height);
+                self.conn.configure_window(window, &aux).is_ok()
+            }
+        };
+
+        sent && self.conn.flush().is_ok()
+    }
+}
+
+fn list_processes(
+    x11: &X11,
+    procfs: &Procfs,
+    matcher: &ClientMatcher,
+) -> Result<Vec<WindowMatch>, String> {
+    let processes = find_client_processes(procfs, matcher);
+    if processes.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let pid_atom = x11.atom("_NET_WM_PID")?;
+    let mut matches = Vec::new();
+
+    for window in x11.client_list()? {
+        let Some(pid) = x11.window_pid(window, pid_atom) else {
+            continue;
+        };
+        let Some(process) = processes.iter().find(|p| p.pid == pid) else {
+            continue;
+        };
+        let Some(geometry) = x11.window_geometry(window) else {
+            continue;
+        };
+
+        if !matcher.matches_size(geometry.width, geometry.height) {
+            continue;
         }
+
+        let classes = x11.window_class(window);
+        let class_matches = if classes.is_empty() {
+            matcher.matches_class("")
+        } else {
+            classes.iter().any(|c| matcher.matches_class(c))
+        };
+        if !class_matches {
+            continue;
+        }
+
+        let title = x11.window_title(window);
+        if !matcher.matches_title(&title) {
+            continue;
+        }
+
+        matches.push(WindowMatch::new(
+            pid,
+            window as isize,
+            process.name.clone(),
+            title,
+            Some(geometry),
+            &process.cmdline.join(" "),
+        ));
+    }
+
+    Ok(matches)
+}
+
+fn bring_window_to_top(x11: &X11, handle: isize) -> bool {
+    let Ok(active) = x11.atom("_NET_ACTIVE_WINDOW") else {
+        return false;
+    };
+
+    let window = handle as Window;
+    // Source indication 1 tells the window manager the request comes from an application.
+    let event = ClientMessageEvent::new(32, window, active, [1u32, 0, 0, 0, 0]);
+
+    x11.conn.map_window(window).is_ok()
+        && x11
+            .conn
+            .send_event(
+                false,
+                x11.root,
+                EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+                event,
+            )
+            .is_ok()
+        && x11.conn.flush().is_ok()
+}
+
+fn capture_window(x11: &X11, window: Window) -> Option<Capture> {
+    let geometry = x11.conn.get_geometry(window).ok()?.reply().ok()?;
+    let image = x11
+        .conn
+        .get_image(
+            ImageFormat::Z_PIXMAP,
+            window,
+            0,
+            0,
+            geometry.width,
+            geometry.height,
+            !0,
+        )
+        .ok()?
+        .reply()
+        .ok()?;
+
+    // 24 and 32 bit visuals are stored as 4 bytes per pixel, BGRX on little endian.
+    let (width, height) = (geometry.width as u32, geometry.height as u32);
+    if image.data.len() != (width * height * 4) as usize {
+        return None;
+    }
+
+    let mut pixels = image.data;
+    for pixel in pixels.chunks_exact_mut(4) {
+        pixel[3] = 0xff;
+    }
+
+    Some(Capture {
+        width,
+        height,
+        pixels,
+    })
+}
+
+pub struct X11Provider {
+    procfs: Procfs,
+    // Kept open across calls, the client watcher checks every selected client every
+    // couple of seconds.
+    x11: Mutex<Option<X11>>,
+}
+
+impl X11Provider {
+    /// Runs `f` on the shared connection, connecting first if there is none. When `f`
+    /// fails the connection is checked and dropped if it broke, so the next call
+    /// reconnects, e.g. after the X server restarted.
+    fn with_x11<T>(&self, f: impl FnOnce(&X11) -> Option<T>) -> Option<T> {
+        let mut x11 = self.x11.lock().unwrap();
+        if x11.is_none() {
+            *x11 = X11::connect().ok();
+        }
+
+        let result = f(x11.as_ref()?);
+        if result.is_none() && !x11.as_ref().is_some_and(X11::is_connected) {
+            *x11 = None;
+        }
+        result
+    }
+}
+
+impl ClientProvider for X11Provider {
+    fn list_clients(&self, matcher: &ClientMatcher) -> Result<Vec<WindowMatch>, String> {
+        let mut error = None;
+        let clients = self.with_x11(|x11| {
+            list_processes(x11, &self.procfs, matcher)
+                .map_err(|e| error = Some(e))
+                .ok()
+        });
+        clients.ok_or_else(|| error.unwrap_or_else(|| "Failed to connect to the X server".into()))
+    }
+
+    fn focus_client(&self, client: &WindowMatch) -> bool {
+        self.with_x11(|x11| bring_window_to_top(x11, client.hwnd).then_some(()))
+            .is_some()
+    }
+
+    fn window_geometry(&self, client: &WindowMatch) -> Option<WindowGeometry> {
+        self.with_x11(|x11| x11.window_geometry(client.hwnd as Window))
+    }
+
+    fn frame_geometry(&self, client: &WindowMatch) -> Option<WindowGeometry> {
+        self.with_x11(|x11| x11.frame_geometry(client.hwnd as Window))
+    }
+
+    fn move_client(&self, client: &WindowMatch, geometry: WindowGeometry) -> bool {
+        self.with_x11(|x11| {
+            x11.move_frame(client.hwnd as Window, geometry)
+                .then_some(())
+        })
+        .is_some()
+    }
+
+    fn capture(&self, client: &WindowMatch) -> Option<Capture> {
+        self.with_x11(|x11| capture_window(x11, client.hwnd as Window))
+    }
+
+    fn is_alive(&self, client: &WindowMatch) -> bool {
+        self.procfs.is_running(client.pid) && self.window_geometry(client).is_some()
+    }
+}
+
+pub fn platform_provider() -> Box<dyn ClientProvider> {
+    Box::new(X11Provider {
+        procfs: Procfs::default(),
+        x11: Mutex::new(None),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::client::MatchRules;
+
+    fn add_process(root: &Path, pid: u32, comm: &str, cmdline: &[&str]) -> std::path::PathBuf {
+        let dir = root.join(pid.to_string());
+        fs::create_dir(&dir).unwrap();
+        fs::write(dir.join("comm"), format!("{}\n", comm)).unwrap();
+        let mut args = cmdline.join("\0").into_bytes();
+        args.push(0);
+        fs::write(dir.join("cmdline"), args).unwrap();
+        fs::write(
+            dir.join("stat"),
+            format!("{} ({}) S 1 {} {} 0", pid, comm, pid, pid),
+        )
+        .unwrap();
+        dir
+    }
+
+    fn matcher(process_names: &[&str]) -> ClientMatcher {
+        ClientMatcher::new(&MatchRules {
+            process_names: process_names.iter().map(|p| p.to_string()).collect(),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn detects_java_clients_from_the_command_line() {
+        let root = tempfile::tempdir().unwrap();
+        add_process(
+            root.path(),
+            100,
+            "java",
+            &[
+                "/usr/lib/jvm/bin/java",
+                "-jar",
+                "/home/me/.runelite/RuneLite.jar",
+            ],
+        );
+        add_process(root.path(), 101, "java", &["java", "-jar", "server.jar"]);
+        add_process(root.path(), 102, "RuneLite", &["./RuneLite"]);
+        let procfs = Procfs::new(root.path());
+
+        assert!(is_client_process(&procfs, 100, "java"));
+        assert!(!is_client_process(&procfs, 101, "java"));
+        assert!(is_client_process(&procfs, 102, "RuneLite"));
+    }
+
+    #[test]
+    fn detects_appimage_clients_from_the_environment() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = add_process(root.path(), 200, "java", &["java", "-cp", "client.jar"]);
+        fs::write(
+            dir.join("environ"),
+            b"HOME=/home/me\0APPIMAGE=/opt/RuneLite.AppImage\0",
+        )
+        .unwrap();
+
+        assert!(is_client_process(&Procfs::new(root.path()), 200, "java"));
+    }
+
+    #[test]
+    fn finds_clients_and_skips_processes_that_vanished() {
+        let root = tempfile::tempdir().unwrap();
+        add_process(root.path(), 300, "java", &["java", "-jar", "RuneLite.jar"]);
+        add_process(root.path(), 301, "bash", &["bash"]);
+        // Listed by read_dir but gone before its files were read.
+        fs::create_dir(root.path().join("302")).unwrap();
+        fs::create_dir(root.path().join("self")).unwrap();
+
+        let clients = find_client_processes(&Procfs::new(root.path()), &matcher(&[]));
+        assert_eq!(clients.len(), 1);
+        assert_eq!(clients[0].pid, 300);
+        assert_eq!(clients[0].name, "java");
+        assert_eq!(clients[0].cmdline, vec!["java", "-jar", "RuneLite.jar"]);
+    }
+
+    #[test]
+    fn process_patterns_replace_the_builtin_detection() {
+        let root = tempfile::tempdir().unwrap();
+        add_process(root.path(), 400, "java", &["java", "-jar", "RuneLite.jar"]);
+        add_process(root.path(), 401, "bolt", &["/usr/bin/bolt"]);
+
+        let clients = find_client_processes(&Procfs::new(root.path()), &matcher(&["^bolt$"]));
+        let pids: Vec<u32> = clients.iter().map(|c| c.pid).collect();
+        assert_eq!(pids, vec![401]);
     }
 }
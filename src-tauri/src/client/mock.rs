@@ -0,0 +1,113 @@
+use std::sync::Mutex;
+
+use super::{Capture, ClientMatcher, ClientProvider, WindowGeometry, WindowMatch};
+
+/// In-memory provider with fake client windows, for tests.
+#[derive(Default)]
+pub struct MockProvider {
+    clients: Mutex<Vec<(WindowMatch, WindowGeometry)>>,
+}
+
+impl MockProvider {
+    pub fn with_clients(count: usize) -> Self {
+        let provider = Self::default();
+        for i in 0..count {
+            let geometry = WindowGeometry {
+                x: 40 * i as i32,
+                y: 40 * i as i32,
+                width: 765,
+                height: 503,
+            };
+            let client = WindowMatch::new(
+                10_000 + i as u32,
+                1 + i as isize,
+                "java.exe".to_string(),
+                format!("RuneLite - Mock {}", i + 1),
+                Some(geometry),
+                &format!("java -jar RuneLite.jar --profile=mock{}", i + 1),
+            );
+            provider.add_client(client, geometry);
+        }
+        provider
+    }
+
+    pub fn add_client(&self, client: WindowMatch, geometry: WindowGeometry) {
+        self.clients.lock().unwrap().push((client, geometry));
+    }
+
+    /// Closes client window `hwnd`, as if the game exited.
+    pub fn remove_client(&self, hwnd: isize) {
+        self.clients.lock().unwrap().retain(|(c, _)| c.hwnd != hwnd);
+    }
+
+    pub fn clients(&self) -> Vec<WindowMatch> {
+        self.clients
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(c, _)| c.clone())
+            .collect()
+    }
+}
+
+impl ClientProvider for MockProvider {
+    fn list_clients(&self, matcher: &ClientMatcher) -> Result<Vec<WindowMatch>, String> {
+        Ok(self
+            .clients
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(c, g)| {
+                matcher.matches_process(&c.name)
+                    && matcher.matches_title(&c.title)
+                    && matcher.matches_size(g.width, g.height)
+            })
+            .map(|(c, _)| c.clone())
+            .collect())
+    }
+
+    fn focus_client(&self, client: &WindowMatch) -> bool {
+        self.is_alive(client)
+    }
+
+    fn window_geometry(&self, client: &WindowMatch) -> Option<WindowGeometry> {
+        self.clients
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(c, _)| c.hwnd == client.hwnd)
+            .map(|(_, g)| *g)
+    }
+
+    fn frame_geometry(&self, client: &WindowMatch) -> Option<WindowGeometry> {
+        self.window_geometry(client)
+    }
+
+    fn move_client(&self, client: &WindowMatch, geometry: WindowGeometry) -> bool {
+        let mut clients = self.clients.lock().unwrap();
+        match clients.iter_mut().find(|(c, _)| c.hwnd == client.hwnd) {
+            Some((_, g)) => {
+                *g = geometry;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn capture(&self, client: &WindowMatch) -> Option<Capture> {
+        let geometry = self.window_geometry(client)?;
+        Some(Capture {
+            width: geometry.width,
+            height: geometry.height,
+            pixels: [0x40, 0x40, 0x40, 0xff].repeat((geometry.width * geometry.height) as usize),
+        })
+    }
+
+    fn is_alive(&self, client: &WindowMatch) -> bool {
+        self.clients
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|(c, _)| c.hwnd == client.hwnd && c.pid == client.pid)
+    }
+}
@@ -1,13 +1,135 @@
+use serde::{Deserialize, Serialize};
+
+// Slot used when a command doesn't name the client it targets.
+pub const DEFAULT_CLIENT: &str = "default";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientKind {
+    Official,
+    RuneLite,
+    #[default]
+    Java,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WindowMatch {
+    pid: u32,
+    pub(crate) hwnd: isize,
+    name: String,
+    #[serde(default)]
+    kind: ClientKind,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    geometry: Option<WindowGeometry>,
+    /// Identifies the client across restarts, independent of its PID.
+    #[serde(default)]
+    pub(crate) fingerprint: String,
+    #[serde(default)]
+    pub(crate) label: Option<String>,
+}
+
+impl WindowMatch {
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// The user's label for the client, falling back to its process name.
+    pub fn display_name(&self) -> String {
+        self.label.clone().unwrap_or_else(|| self.name.clone())
+    }
+
+    /// `origin` is whatever best describes how the client was started: its command
+    /// line where available, otherwise its executable path.
+    pub fn new(
+        pid: u32,
+        hwnd: isize,
+        name: String,
+        title: String,
+        geometry: Option<WindowGeometry>,
+        origin: &str,
+    ) -> Self {
+        Self {
+            pid,
+            hwnd,
+            kind: classify(&name, &title, origin),
+            fingerprint: fingerprint(origin),
+            name,
+            title,
+            geometry,
+            label: None,
+        }
+    }
+}
+
+fn classify(name: &str, title: &str, origin: &str) -> ClientKind {
+    let name = name.to_lowercase();
+    let origin = origin.to_lowercase();
+
+    if name.contains("runelite") || origin.contains("runelite") || title.starts_with("RuneLite") {
+        ClientKind::RuneLite
+    } else if name.contains("osclient") || title == "Old School RuneScape" {
+        ClientKind::Official
+    } else {
+        ClientKind::Java
+    }
+}
+
+// FNV-1a, spelled out because the std hashers aren't guaranteed to be stable across releases
+// and fingerprints are persisted.
+fn fingerprint(origin: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in origin.as_bytes() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+pub trait ClientProvider: Send + Sync {
+    fn list_clients(&self, matcher: &ClientMatcher) -> Result<Vec<WindowMatch>, String>;
+    fn focus_client(&self, client: &WindowMatch) -> bool;
+    fn window_geometry(&self, client: &WindowMatch) -> Option<WindowGeometry>;
+    /// Geometry of the client's top-level window, including decorations.
+    fn frame_geometry(&self, client: &WindowMatch) -> Option<WindowGeometry>;
+    /// Moves and resizes the client's top-level window, `geometry` includes decorations
+    /// like `frame_geometry`.
+    fn move_client(&self, client: &WindowMatch, geometry: WindowGeometry) -> bool;
+    /// Grabs the current contents of the client's window.
+    fn capture(&self, client: &WindowMatch) -> Option<Capture>;
+    fn is_alive(&self, client: &WindowMatch) -> bool;
+}
+
+mod capture;
+mod layout;
+#[cfg(test)]
+mod mock;
+mod rules;
+mod spawn;
+mod watcher;
+pub use self::capture::Capture;
+pub use self::layout::WindowLayout;
+#[cfg(test)]
+pub use self::mock::MockProvider;
+pub use self::rules::{ClientMatcher, MatchRules};
+pub use self::spawn::spawn_client;
+pub use self::watcher::start_client_watcher;
+
 #[cfg(target_os = "windows")]
 mod windows;
 #[cfg(target_os = "windows")]
-pub use self::windows::bring_window_to_top;
-#[cfg(target_os = "windows")]
-pub use self::windows::list_processes;
-#[cfg(target_os = "windows")]
-pub use self::windows::WindowMatch;
+pub use self::windows::platform_provider;
 
 #[cfg(target_os = "linux")]
 mod linux;
 #[cfg(target_os = "linux")]
-pub use self::linux::list_processes;
+pub use self::linux::platform_provider;
@@ -0,0 +1,85 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// User configurable rules deciding which windows count as game clients.
+/// Empty lists accept anything.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MatchRules {
+    /// Window classes of the game canvas. On Windows this is the child window class,
+    /// on Linux either part of the top-level window's `WM_CLASS`.
+    pub class_names: Vec<String>,
+    /// Regexes matched against the process name. When set they replace the built-in
+    /// Java client detection on Linux.
+    pub process_names: Vec<String>,
+    /// Regexes matched against the title of the top-level window.
+    pub title_patterns: Vec<String>,
+    pub min_width: u32,
+    pub min_height: u32,
+}
+
+impl Default for MatchRules {
+    fn default() -> Self {
+        Self {
+            class_names: if cfg!(target_os = "windows") {
+                vec!["SunAwtCanvas".to_string()]
+            } else {
+                Vec::new()
+            },
+            process_names: Vec::new(),
+            title_patterns: Vec::new(),
+            min_width: 100,
+            min_height: 100,
+        }
+    }
+}
+
+/// `MatchRules` with the regexes compiled.
+#[derive(Debug, Clone)]
+pub struct ClientMatcher {
+    class_names: Vec<String>,
+    process_names: Vec<Regex>,
+    title_patterns: Vec<Regex>,
+    min_width: u32,
+    min_height: u32,
+}
+
+fn compile(patterns: &[String]) -> Result<Vec<Regex>, String> {
+    patterns
+        .iter()
+        .map(|p| Regex::new(p).map_err(|e| format!("Invalid pattern {:?}: {}", p, e)))
+        .collect()
+}
+
+impl ClientMatcher {
+    pub fn new(rules: &MatchRules) -> Result<Self, String> {
+        Ok(Self {
+            class_names: rules.class_names.clone(),
+            process_names: compile(&rules.process_names)?,
+            title_patterns: compile(&rules.title_patterns)?,
+            min_width: rules.min_width,
+            min_height: rules.min_height,
+        })
+    }
+
+    pub fn has_process_patterns(&self) -> bool {
+        !self.process_names.is_empty()
+    }
+
+    pub fn matches_class(&self, class: &str) -> bool {
+        self.class_names.is_empty() || self.class_names.iter().any(|c| c == class)
+    }
+
+    pub fn matches_process(&self, name: &str) -> bool {
+        self.process_names.is_empty() || self.process_names.iter().any(|r| r.is_match(name))
+    }
+
+    pub fn matches_title(&self, title: &str) -> bool {
+        self.title_patterns.is_empty() || self.title_patterns.iter().any(|r| r.is_match(title))
+    }
+
+    // Strictly greater, like the original hardcoded 100x100 check.
+    pub fn matches_size(&self, width: u32, height: u32) -> bool {
+        width > self.min_width && height > self.min_height
+    }
+}
@@ -0,0 +1,208 @@
+use std::{
+    collections::HashSet,
+    path::PathBuf,
+    process::Command,
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde_json::json;
+use tauri::{Emitter, Manager};
+
+use crate::{profiles::ClientAccount, settings::SettingsStore, LauncherVariables};
+
+use super::{ClientMatcher, ClientProvider, WindowMatch};
+
+const WINDOW_TIMEOUT: Duration = Duration::from_secs(120);
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Starts a game client and, once its canvas shows up, selects it as the target of `slot`.
+pub fn spawn_client(
+    app: tauri::AppHandle,
+    path: PathBuf,
+    account: ClientAccount,
+    slot: String,
+) -> Result<u32, String> {
+    let matcher = ClientMatcher::new(&app.state::<SettingsStore>().get().client_rules)?;
+
+    let provider = app.state::<Box<dyn ClientProvider>>();
+    let known: HashSet<isize> = provider
+        .list_clients(&matcher)
+        .unwrap_or_default()
+        .iter()
+        .map(|c| c.hwnd)
+        .collect();
+
+    let mut cmd = Command::new(&path);
+    cmd.args(&account.args).envs(&account.env);
+    if let Some(dir) = path.parent() {
+        cmd.current_dir(dir);
+    }
+
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to launch {:?}: {}", path, e))?;
+    let pid = child.id();
+    println!(
+        "Launched client {:?} (PID: {}) for slot {}",
+        path, pid, slot
+    );
+
+    {
+        let launcher = app.state::<Mutex<LauncherVariables>>();
+        let launcher = launcher.lock().unwrap();
+        launcher
+            .game_clients
+            .lock()
+            .unwrap()
+            .insert(pid, slot.clone());
+    }
+
+    thread::spawn(move || {
+        let client = wait_for_window(&app, &matcher, &known, pid, &slot);
+        let _ = child.wait();
+        forget_game_client(&app, pid);
+
+        // Launchers that hand off to another process exit while the client keeps running,
+        // the client watcher reports that one once its window is gone.
+        let handed_off = client.is_some_and(|client| {
+            client.pid() != pid
+                && app
+                    .try_state::<Box<dyn ClientProvider>>()
+                    .is_some_and(|provider| provider.is_alive(&client))
+        });
+        if !handed_off {
+            let _ = app.emit("client-exited", pid);
+        }
+    });
+
+    Ok(pid)
+}
+
+fn forget_game_client(app: &tauri::AppHandle, pid: u32) {
+    if let Some(launcher) = app.try_state::<Mutex<LauncherVariables>>() {
+        let launcher = launcher.lock().unwrap();
+        launcher.game_clients.lock().unwrap().remove(&pid);
+    }
+}
+
+/// Picks the window of the client started as `pid`. Launchers like RuneLite's may hand
+/// off to a child JVM, so this falls back to a canvas that wasn't there before the launch,
+/// isn't selected or bound already and doesn't belong to another launched client.
+fn pick_window(
+    clients: &[WindowMatch],
+    pid: u32,
+    known: &HashSet<isize>,
+    taken: &HashSet<isize>,
+    launched: &HashSet<u32>,
+) -> Option<WindowMatch> {
+    let free = |c: &&WindowMatch| !taken.contains(&c.hwnd);
+
+    clients
+        .iter()
+        .filter(free)
+        .find(|c| c.pid() == pid)
+        .or_else(|| {
+            clients.iter().filter(free).find(|c| {
+                !known.contains(&c.hwnd) && (c.pid() == pid || !launched.contains(&c.pid()))
+            })
+        })
+        .cloned()
+}
+
+/// Polls for the window of client `pid` and selects it for `slot`. The window is claimed
+/// under the launcher lock, so concurrent launches can't pick the same one.
+fn wait_for_window(
+    app: &tauri::AppHandle,
+    matcher: &ClientMatcher,
+    known: &HashSet<isize>,
+    pid: u32,
+    slot: &str,
+) -> Option<WindowMatch> {
+    let started = Instant::now();
+
+    while started.elapsed() < WINDOW_TIMEOUT {
+        thread::sleep(POLL_INTERVAL);
+
+        let provider = app.try_state::<Box<dyn ClientProvider>>()?;
+        let Ok(clients) = provider.list_clients(matcher) else {
+            continue;
+        };
+
+        let claimed = {
+            let launcher = app.try_state::<Mutex<LauncherVariables>>()?;
+            let mut launcher = launcher.lock().unwrap();
+            let taken: HashSet<isize> = launcher
+                .clients
+                .values()
+                .chain(launcher.bindings.lock().unwrap().values())
+                .map(|c| c.hwnd)
+                .collect();
+            let launched: HashSet<u32> = launcher
+                .game_clients
+                .lock()
+                .unwrap()
+                .keys()
+                .copied()
+                .collect();
+
+            let claimed = pick_window(&clients, pid, known, &taken, &launched);
+            if let Some(client) = &claimed {
+                launcher.clients.insert(slot.to_string(), client.clone());
+            }
+            claimed
+        };
+
+        if let Some(client) = claimed {
+            let _ = app.emit("client-ready", json!({ "id": slot, "client": client }));
+            return Some(client);
+        }
+    }
+
+    println!("Timed out waiting for the window of client {}", pid);
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::MockProvider;
+
+    #[test]
+    fn prefers_the_launched_process() {
+        let clients = MockProvider::with_clients(2).clients();
+        let pid = clients[1].pid();
+
+        let client = pick_window(
+            &clients,
+            pid,
+            &HashSet::new(),
+            &HashSet::new(),
+            &HashSet::new(),
+        );
+        assert_eq!(client.unwrap().hwnd, clients[1].hwnd);
+    }
+
+    #[test]
+    fn falls_back_to_a_new_unclaimed_window() {
+        let clients = MockProvider::with_clients(3).clients();
+        let known = HashSet::from([clients[0].hwnd]);
+        let taken = HashSet::from([clients[1].hwnd]);
+
+        // The launcher handed off, so none of the windows belong to its PID.
+        let client = pick_window(&clients, 1, &known, &taken, &HashSet::new());
+        assert_eq!(client.unwrap().hwnd, clients[2].hwnd);
+
+        let taken = HashSet::from([clients[1].hwnd, clients[2].hwnd]);
+        assert!(pick_window(&clients, 1, &known, &taken, &HashSet::new()).is_none());
+    }
+
+    #[test]
+    fn skips_windows_of_other_launched_clients() {
+        let clients = MockProvider::with_clients(1).clients();
+        let launched = HashSet::from([clients[0].pid()]);
+
+        assert!(pick_window(&clients, 1, &HashSet::new(), &HashSet::new(), &launched).is_none());
+    }
+}
@@ -0,0 +1,145 @@
+use std::{collections::HashMap, sync::Mutex, thread, time::Duration};
+
+use serde_json::json;
+use tauri::{Emitter, Manager};
+
+use crate::{
+    scripts::{stop_script, ExitReason},
+    settings::SettingsStore,
+    LauncherVariables,
+};
+
+use super::{ClientProvider, WindowMatch};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Selected clients whose window or process is gone.
+fn find_lost(
+    provider: &dyn ClientProvider,
+    clients: HashMap<String, WindowMatch>,
+) -> Vec<(String, WindowMatch)> {
+    clients
+        .into_iter()
+        .filter(|(_, client)| !provider.is_alive(client))
+        .collect()
+}
+
+/// Empties the slots that still hold their lost client and returns those. A slot that
+/// was given another client since the snapshot is left alone.
+fn remove_lost(
+    clients: &mut HashMap<String, WindowMatch>,
+    lost: Vec<(String, WindowMatch)>,
+) -> Vec<(String, WindowMatch)> {
+    lost.into_iter()
+        .filter(|(slot, client)| {
+            let current = clients.get(slot).is_some_and(|c| c.hwnd == client.hwnd);
+            if current {
+                clients.remove(slot);
+            }
+            current
+        })
+        .collect()
+}
+
+/// Scripts bound to one of the `lost` clients.
+fn bound_to(bindings: &HashMap<u32, WindowMatch>, lost: &[(String, WindowMatch)]) -> Vec<u32> {
+    bindings
+        .iter()
+        .filter(|(_, c)| lost.iter().any(|(_, l)| l.hwnd == c.hwnd))
+        .map(|(id, _)| *id)
+        .collect()
+}
+
+/// Periodically checks the selected clients, drops the ones whose window or process
+/// is gone and emits `client-lost` for each of them.
+pub fn start_client_watcher(app: tauri::AppHandle) {
+    thread::spawn(move || loop {
+        thread::sleep(CHECK_INTERVAL);
+
+        let Some(launcher) = app.try_state::<Mutex<LauncherVariables>>() else {
+            continue;
+        };
+        let Some(provider) = app.try_state::<Box<dyn ClientProvider>>() else {
+            continue;
+        };
+
+        let clients = {
+            let guard = launcher.lock().unwrap();
+            guard.clients.clone()
+        };
+
+        let lost = find_lost(provider.as_ref(), clients);
+
+        if lost.is_empty() {
+            continue;
+        }
+
+        let stop_scripts = app.state::<SettingsStore>().get().stop_on_client_lost;
+        let (removed, bound) = {
+            let mut guard = launcher.lock().unwrap();
+            // Scripts on a dead window are stopped even if its slot was reassigned.
+            let bound = bound_to(&guard.bindings.lock().unwrap(), &lost);
+            let removed = remove_lost(&mut guard.clients, lost);
+
+            (removed, bound)
+        };
+
+        for (slot, client) in &removed {
+            println!("Client {} was lost", slot);
+            let _ = app.emit("client-lost", json!({ "id": slot, "client": client }));
+        }
+
+        if stop_scripts {
+            for id in bound {
+                let _ = stop_script(&app, id, ExitReason::ClientLost);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::MockProvider;
+
+    #[test]
+    fn finds_lost_clients_and_their_scripts() {
+        let provider = MockProvider::with_clients(2);
+        let [first, second] = <[WindowMatch; 2]>::try_from(provider.clients()).unwrap();
+        let clients = HashMap::from([
+            ("a".to_string(), first.clone()),
+            ("b".to_string(), second.clone()),
+        ]);
+        let bindings = HashMap::from([(1, first.clone()), (2, second.clone()), (3, first.clone())]);
+
+        assert!(find_lost(&provider, clients.clone()).is_empty());
+
+        provider.remove_client(first.hwnd);
+        let lost = find_lost(&provider, clients);
+        assert_eq!(lost.len(), 1);
+        assert_eq!(lost[0].0, "a");
+
+        let mut bound = bound_to(&bindings, &lost);
+        bound.sort();
+        assert_eq!(bound, vec![1, 3]);
+    }
+
+    #[test]
+    fn keeps_slots_reassigned_since_the_snapshot() {
+        let provider = MockProvider::with_clients(2);
+        let [first, second] = <[WindowMatch; 2]>::try_from(provider.clients()).unwrap();
+        let snapshot = HashMap::from([("a".to_string(), first.clone())]);
+
+        provider.remove_client(first.hwnd);
+        let lost = find_lost(&provider, snapshot);
+
+        // The user picked another client for the slot while the watcher was checking.
+        let mut clients = HashMap::from([("a".to_string(), second.clone())]);
+        assert!(remove_lost(&mut clients, lost.clone()).is_empty());
+        assert_eq!(clients["a"].hwnd, second.hwnd);
+
+        let mut clients = HashMap::from([("a".to_string(), first)]);
+        assert_eq!(remove_lost(&mut clients, lost).len(), 1);
+        assert!(clients.is_empty());
+    }
+}
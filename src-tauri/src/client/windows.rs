@@ -1,30 +1,33 @@
-use serde::{Deserialize, Serialize};
-use windows::core::BOOL;
-use windows::Win32::Foundation::{CloseHandle, HWND, LPARAM, RECT};
+use windows::core::{BOOL, PWSTR};
+use windows::Wdk::System::Threading::{NtQueryInformationProcess, ProcessCommandLineInformation};
+use windows::Win32::Foundation::{CloseHandle, HWND, LPARAM, RECT, UNICODE_STRING};
+use windows::Win32::Graphics::Gdi::{
+    BitBlt, CreateCompatibleBitmap, CreateCompatibleDC, DeleteDC, DeleteObject, GetDC, GetDIBits,
+    ReleaseDC, SelectObject, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS, SRCCOPY,
+};
 use windows::Win32::System::Diagnostics::ToolHelp::{
     CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W, TH32CS_SNAPPROCESS,
 };
+use windows::Win32::System::Threading::{
+    OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+};
 use windows::Win32::UI::WindowsAndMessaging::{
-    EnumChildWindows, EnumWindows, GetAncestor, GetClassNameW, GetWindowRect,
-    GetWindowThreadProcessId, IsIconic, SetForegroundWindow, ShowWindow, GA_ROOT, SW_RESTORE,
-    SW_SHOW,
+    EnumChildWindows, EnumWindows, GetAncestor, GetClassNameW, GetClientRect, GetWindowRect,
+    GetWindowTextW, GetWindowThreadProcessId, IsIconic, IsWindow, SetForegroundWindow,
+    SetWindowPos, ShowWindow, GA_ROOT, SWP_NOACTIVATE, SWP_NOZORDER, SW_RESTORE, SW_SHOW,
 };
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct WindowMatch {
-    pid: u32,
-    pub(crate) hwnd: isize,
-    name: String,
-}
+use super::{Capture, ClientMatcher, ClientProvider, WindowGeometry, WindowMatch};
 
-struct EnumContext {
+struct EnumContext<'a> {
+    matcher: &'a ClientMatcher,
     target_pid: u32,
     process_name: String,
     matches: Vec<WindowMatch>,
     found: bool,
 }
 
-pub fn list_processes() -> Result<Vec<WindowMatch>, String> {
+pub fn list_processes(matcher: &ClientMatcher) -> Result<Vec<WindowMatch>, String> {
     let mut all_matches = Vec::new();
 
     unsafe {
@@ -38,19 +41,22 @@ pub fn list_processes() -> Result<Vec<WindowMatch>, String> {
             loop {
                 let process_name = string_from_u16_slice(&entry.szExeFile);
 
-                let mut context = EnumContext {
-                    target_pid: entry.th32ProcessID,
-                    process_name: process_name.clone(),
-                    matches: Vec::new(),
-                    found: false,
-                };
+                if matcher.matches_process(&process_name) {
+                    let mut context = EnumContext {
+                        matcher,
+                        target_pid: entry.th32ProcessID,
+                        process_name: process_name.clone(),
+                        matches: Vec::new(),
+                        found: false,
+                    };
 
-                let _ = EnumWindows(
-                    Some(enum_window_callback),
-                    LPARAM(&mut context as *mut EnumContext as isize),
-                );
+                    let _ = EnumWindows(
+                        Some(enum_window_callback),
+                        LPARAM(&mut context as *mut EnumContext as isize),
+                    );
 
-                all_matches.extend(context.matches);
+                    all_matches.extend(context.matches);
+                }
 
                 if Process32NextW(snapshot, &mut entry).is_err() {
                     break;
@@ -108,46 +114,129 @@ unsafe fn check_and_add_if_match(hwnd: HWND, context: &mut EnumContext) -> bool
     }
 
     let name = String::from_utf16_lossy(&class_name[..len as usize]);
-    if name != "SunAwtCanvas" {
+    if !context.matcher.matches_class(&name) {
         return false;
     }
 
-    let mut rect = RECT::default();
-    if GetWindowRect(hwnd, &mut rect).is_err() {
+    let Some(geometry) = window_rect(hwnd.0 as isize) else {
         return false;
-    }
+    };
 
-    let width = rect.right - rect.left;
-    let height = rect.bottom - rect.top;
+    if !context
+        .matcher
+        .matches_size(geometry.width, geometry.height)
+    {
+        return false;
+    }
 
-    if width <= 100 || height <= 100 {
+    let title = window_title(hwnd);
+    if !context.matcher.matches_title(&title) {
         return false;
     }
 
-    context.matches.push(WindowMatch {
-        pid: context.target_pid,
-        hwnd: hwnd.0 as isize,
-        name: context.process_name.clone(),
-    });
+    // The image path is the same for every client of a kind, the command line carries the
+    // profile or account that tells them apart.
+    let origin = process_command_line(context.target_pid)
+        .or_else(|| process_image_path(context.target_pid))
+        .unwrap_or(context.process_name.clone());
+
+    context.matches.push(WindowMatch::new(
+        context.target_pid,
+        hwnd.0 as isize,
+        context.process_name.clone(),
+        title,
+        Some(geometry),
+        &origin,
+    ));
 
     context.found = true;
     true
 }
 
+unsafe fn window_title(hwnd: HWND) -> String {
+    let root = GetAncestor(hwnd, GA_ROOT);
+    let mut title = [0u16; 512];
+    let len = GetWindowTextW(root, &mut title);
+    String::from_utf16_lossy(&title[..len.max(0) as usize])
+}
+
+fn process_image_path(pid: u32) -> Option<String> {
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        let mut path = [0u16; 1024];
+        let mut size = path.len() as u32;
+
+        let result = QueryFullProcessImageNameW(
+            handle,
+            PROCESS_NAME_WIN32,
+            PWSTR(path.as_mut_ptr()),
+            &mut size,
+        );
+        let _ = CloseHandle(handle);
+        result.ok()?;
+
+        Some(String::from_utf16_lossy(&path[..size as usize]))
+    }
+}
+
+fn process_command_line(pid: u32) -> Option<String> {
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        let mut size = 0u32;
+        // The first call only reports the size of the UNICODE_STRING and the text after it.
+        let _ = NtQueryInformationProcess(
+            handle,
+            ProcessCommandLineInformation,
+            std::ptr::null_mut(),
+            0,
+            &mut size,
+        );
+
+        // u64s keep the buffer aligned for the UNICODE_STRING at its start.
+        let mut buffer = vec![0u64; (size as usize).div_ceil(8)];
+        let status = NtQueryInformationProcess(
+            handle,
+            ProcessCommandLineInformation,
+            buffer.as_mut_ptr().cast(),
+            size,
+            &mut size,
+        );
+        let _ = CloseHandle(handle);
+        if status.is_err() || size == 0 {
+            return None;
+        }
+
+        let command_line = &*(buffer.as_ptr() as *const UNICODE_STRING);
+        if command_line.Buffer.is_null() {
+            return None;
+        }
+        let chars =
+            std::slice::from_raw_parts(command_line.Buffer.0, command_line.Length as usize / 2);
+        Some(String::from_utf16_lossy(chars))
+    }
+}
+
 fn string_from_u16_slice(slice: &[u16]) -> String {
     let len = slice.iter().position(|&c| c == 0).unwrap_or(slice.len());
     String::from_utf16_lossy(&slice[..len])
 }
 
-pub fn bring_window_to_top(handle: isize) -> bool {
+fn root_window(handle: isize) -> Option<HWND> {
     let hwnd_child = HWND(handle as *mut core::ffi::c_void);
+    let hwnd_root = unsafe { GetAncestor(hwnd_child, GA_ROOT) };
+    if hwnd_root.0.is_null() {
+        None
+    } else {
+        Some(hwnd_root)
+    }
+}
 
-    unsafe {
-        let hwnd_root = GetAncestor(hwnd_child, GA_ROOT);
-        if hwnd_root.0.is_null() {
-            return false;
-        }
+pub fn bring_window_to_top(handle: isize) -> bool {
+    let Some(hwnd_root) = root_window(handle) else {
+        return false;
+    };
 
+    unsafe {
         if IsIconic(hwnd_root).as_bool() {
             let _ = ShowWindow(hwnd_root, SW_RESTORE);
         } else {
@@ -157,3 +246,160 @@ pub fn bring_window_to_top(handle: isize) -> bool {
         SetForegroundWindow(hwnd_root).as_bool()
     }
 }
+
+fn window_rect(handle: isize) -> Option<WindowGeometry> {
+    let hwnd = HWND(handle as *mut core::ffi::c_void);
+    let mut rect = RECT::default();
+
+    unsafe { GetWindowRect(hwnd, &mut rect).ok()? };
+
+    Some(WindowGeometry {
+        x: rect.left,
+        y: rect.top,
+        width: (rect.right - rect.left).max(0) as u32,
+        height: (rect.bottom - rect.top).max(0) as u32,
+    })
+}
+
+fn capture_window(handle: isize) -> Option<Capture> {
+    let hwnd = HWND(handle as *mut core::ffi::c_void);
+    let mut rect = RECT::default();
+    unsafe { GetClientRect(hwnd, &mut rect).ok()? };
+
+    let (width, height) = (rect.right - rect.left, rect.bottom - rect.top);
+    if width <= 0 || height <= 0 {
+        return None;
+    }
+
+    unsafe {
+        let window_dc = GetDC(Some(hwnd));
+        if window_dc.is_invalid() {
+            return None;
+        }
+        let memory_dc = CreateCompatibleDC(Some(window_dc));
+        let bitmap = CreateCompatibleBitmap(window_dc, width, height);
+        let previous = SelectObject(memory_dc, bitmap.into());
+
+        let copied = BitBlt(
+            memory_dc,
+            0,
+            0,
+            width,
+            height,
+            Some(window_dc),
+            0,
+            0,
+            SRCCOPY,
+        )
+        .is_ok();
+        SelectObject(memory_dc, previous);
+
+        let mut info = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: width,
+                // Negative for top-down rows.
+                biHeight: -height,
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut pixels = vec![0u8; (width * height * 4) as usize];
+        let rows = GetDIBits(
+            memory_dc,
+            bitmap,
+            0,
+            height as u32,
+            Some(pixels.as_mut_ptr() as *mut core::ffi::c_void),
+            &mut info,
+            DIB_RGB_COLORS,
+        );
+
+        let _ = DeleteObject(bitmap.into());
+        let _ = DeleteDC(memory_dc);
+        ReleaseDC(Some(hwnd), window_dc);
+
+        if !copied || rows == 0 {
+            return None;
+        }
+
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel[3] = 0xff;
+        }
+
+        Some(Capture {
+            width: width as u32,
+            height: height as u32,
+            pixels,
+        })
+    }
+}
+
+pub struct WindowsProvider;
+
+impl ClientProvider for WindowsProvider {
+    fn list_clients(&self, matcher: &ClientMatcher) -> Result<Vec<WindowMatch>, String> {
+        list_processes(matcher)
+    }
+
+    fn focus_client(&self, client: &WindowMatch) -> bool {
+        bring_window_to_top(client.hwnd)
+    }
+
+    fn window_geometry(&self, client: &WindowMatch) -> Option<WindowGeometry> {
+        window_rect(client.hwnd)
+    }
+
+    fn frame_geometry(&self, client: &WindowMatch) -> Option<WindowGeometry> {
+        let root = root_window(client.hwnd)?;
+        window_rect(root.0 as isize)
+    }
+
+    fn move_client(&self, client: &WindowMatch, geometry: WindowGeometry) -> bool {
+        let Some(root) = root_window(client.hwnd) else {
+            return false;
+        };
+
+        unsafe {
+            if IsIconic(root).as_bool() {
+                let _ = ShowWindow(root, SW_RESTORE);
+            }
+
+            SetWindowPos(
+                root,
+                None,
+                geometry.x,
+                geometry.y,
+                geometry.width as i32,
+                geometry.height as i32,
+                SWP_NOZORDER | SWP_NOACTIVATE,
+            )
+            .is_ok()
+        }
+    }
+
+    fn capture(&self, client: &WindowMatch) -> Option<Capture> {
+        capture_window(client.hwnd)
+    }
+
+    fn is_alive(&self, client: &WindowMatch) -> bool {
+        let hwnd = HWND(client.hwnd as *mut core::ffi::c_void);
+
+        unsafe {
+            if !IsWindow(Some(hwnd)).as_bool() {
+                return false;
+            }
+
+            let mut process_id = 0;
+            GetWindowThreadProcessId(hwnd, Some(&mut process_id));
+            process_id == client.pid
+        }
+    }
+}
+
+pub fn platform_provider() -> Box<dyn ClientProvider> {
+    Box::new(WindowsProvider)
+}
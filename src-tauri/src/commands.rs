@@ -1,8 +1,12 @@
 use std::{
-    fs::{create_dir_all, metadata, remove_dir_all, remove_file, set_permissions, File},
+    collections::HashMap,
+    fs::{
+        create_dir_all, metadata, read_to_string, remove_dir_all, remove_file, set_permissions,
+        write, File,
+    },
     io::Write,
     net::TcpListener,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc, Mutex,
@@ -11,61 +15,58 @@ use std::{
 };
 
 use serde_json::json;
-use tauri::{ipc::Channel, Emitter, Manager, State};
+use tauri::{ipc::Channel, Manager, State};
 use tauri_plugin_http::reqwest::Client;
-use tauri_plugin_store::StoreExt;
 
 use crate::{
-    client::{bring_window_to_top, list_processes, WindowMatch},
-    server::handle_client,
-    simba::{
-        ensure_simba_directories, read_plugins_version, run_simba, run_simba_script,
-        sync_plugins_repo,
+    client::{
+        spawn_client, ClientMatcher, ClientProvider, MatchRules, WindowGeometry, WindowLayout,
+        WindowMatch, DEFAULT_CLIENT,
     },
+    control::{generate_token, ControlSettings},
+    history::{self, DailyRuntime, HistoryQuery, RunRecord},
+    metrics::MetricsSettings,
+    profiles::{ClientAccount, LaunchProfile},
+    progress::ScriptProgress,
+    scripts::{launch_script, new_script_id, stop_script, ExitReason, LaunchRequest, ScriptRecord},
+    server::handle_client,
+    settings::{SettingChange, SettingsStore},
+    simba::{ensure_simba_directories, read_plugins_version, run_simba, sync_plugins_repo},
+    stats::ScriptStats,
+    triggers::{dry_run, Trigger, TriggerMatch, TriggerRule},
+    webhooks::{test_webhook, Webhook},
     LauncherVariables,
 };
 
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 #[tauri::command]
-pub fn get_dev_mode(launcher_vars: State<'_, Mutex<LauncherVariables>>) -> bool {
-    let launcher_vars = launcher_vars.lock().unwrap();
-    launcher_vars.devmode
+pub fn get_dev_mode(settings: State<'_, SettingsStore>) -> bool {
+    settings.get().devmode
 }
 
 #[tauri::command]
-pub fn set_dev_mode(
-    app: tauri::AppHandle,
-    launcher: State<'_, Mutex<LauncherVariables>>,
-    state: bool,
-) {
-    let mut launcher = launcher.lock().unwrap();
-    launcher.devmode = state;
+pub fn set_dev_mode(settings: State<'_, SettingsStore>, state: bool) {
+    settings.update(|settings| settings.devmode = state);
+}
 
-    let store = app
-        .store("settings.json")
-        .expect("Failed to retrieve settings.json store!");
-    store.set("devmode", state);
+#[tauri::command]
+pub fn get_dev_updates(settings: State<'_, SettingsStore>) -> bool {
+    settings.get().dev_updates
 }
 
 #[tauri::command]
-pub fn get_dev_updates(launcher: State<'_, Mutex<LauncherVariables>>) -> bool {
-    let launcher_vars = launcher.lock().unwrap();
-    launcher_vars.dev_updates
+pub fn set_dev_updates(settings: State<'_, SettingsStore>, state: bool) {
+    settings.update(|settings| settings.dev_updates = state);
 }
 
 #[tauri::command]
-pub fn set_dev_updates(
-    app: tauri::AppHandle,
-    launcher_vars: State<'_, Mutex<LauncherVariables>>,
-    state: bool,
-) {
-    let mut launcher_vars = launcher_vars.lock().unwrap();
-    launcher_vars.dev_updates = state;
+pub fn get_stop_on_client_lost(settings: State<'_, SettingsStore>) -> bool {
+    settings.get().stop_on_client_lost
+}
 
-    let store = app
-        .store("settings.json")
-        .expect("Failed to retrieve settings.json store!");
-    store.set("dev_updates", state);
+#[tauri::command]
+pub fn set_stop_on_client_lost(settings: State<'_, SettingsStore>, state: bool) {
+    settings.update(|settings| settings.stop_on_client_lost = state);
 }
 
 #[tauri::command]
@@ -74,6 +75,8 @@ pub fn get_executable_path(launcher: State<'_, Mutex<LauncherVariables>>, exe: S
     match exe.as_str() {
         "simba" => paths.simba.to_str().unwrap().to_string(),
         "devsimba" => paths.devsimba.to_str().unwrap().to_string(),
+        "runelite" => paths.runelite.to_str().unwrap().to_string(),
+        "osclient" => paths.osclient.to_str().unwrap().to_string(),
         _ => paths.simba.to_str().unwrap().to_string(),
     }
 }
@@ -89,13 +92,14 @@ pub fn set_executable_path(
     match exe.as_str() {
         "simba" => paths.simba = PathBuf::from(path.clone()),
         "devsimba" => paths.devsimba = PathBuf::from(path.clone()),
-        _ => {}
+        "runelite" => paths.runelite = PathBuf::from(path.clone()),
+        "osclient" => paths.osclient = PathBuf::from(path.clone()),
+        _ => return,
     }
 
-    let store = app
-        .store("settings.json")
-        .expect("Failed to retrieve settings.json store!");
-    store.set("paths", json!({exe.as_str(): path}));
+    app.state::<SettingsStore>().update(|settings| {
+        settings.paths.insert(exe, PathBuf::from(path));
+    });
 }
 
 #[tauri::command]
@@ -237,7 +241,9 @@ pub async fn run_executable(
             let _ = ensure_simba_directories(&path);
             let plugins_path = path.join("Plugins").join("wasp-plugins");
             tauri::async_runtime::spawn(async move {
-                let _ = sync_plugins_repo(&plugins_path);
+                if let Err(e) = sync_plugins_repo(&plugins_path).await {
+                    eprintln!("{}", e);
+                }
             });
         };
 
@@ -251,103 +257,148 @@ pub async fn run_executable(
 #[tauri::command]
 pub async fn run_script(
     app: tauri::AppHandle,
-    launcher: State<'_, Mutex<LauncherVariables>>,
     args: Vec<String>,
+    client: Option<String>,
+    profile: Option<String>,
+    force: Option<bool>,
     channel: Channel<String>,
-) -> Result<String, String> {
-    let (simba_path, hwnd) = {
-        let guard = launcher.lock().unwrap();
-        match &guard.client {
-            Some(client) => (guard.simba.clone(), client.hwnd),
-            None => return Err("Client is null".to_string()),
-        }
+) -> Result<u32, String> {
+    let request = LaunchRequest {
+        id: new_script_id(&app),
+        args,
+        client,
+        profile,
+        force: force.unwrap_or(false),
+        channel,
+        restarts: 0,
     };
+    launch_script(&app, request).await
+}
 
-    let id = channel.id();
-    let process = run_simba_script(simba_path, hwnd, args, channel).await?;
-
-    let shared_process = Arc::new(Mutex::new(Some(process)));
-
-    let guard = launcher.lock().unwrap();
-    guard
-        .scripts
-        .lock()
-        .unwrap()
-        .insert(id, shared_process.clone());
+#[tauri::command]
+pub async fn kill_script(app: tauri::AppHandle, id: u32) -> Result<String, String> {
+    stop_script(&app, id, ExitReason::Killed)
+}
 
-    let app_clone = app.clone();
+#[tauri::command]
+pub async fn get_running_scripts(
+    launcher: State<'_, Mutex<LauncherVariables>>,
+) -> Result<Vec<ScriptRecord>, String> {
+    let launcher = launcher.lock().unwrap();
+    let records = launcher.registry.lock().unwrap().records();
+    Ok(records)
+}
 
-    std::thread::spawn(move || loop {
-        let status = {
-            let mut inner_guard = shared_process.lock().unwrap();
-            if let Some(child) = inner_guard.as_mut() {
-                child.try_wait()
-            } else {
-                return;
-            }
-        };
+#[tauri::command]
+pub async fn get_script_stats(
+    launcher: State<'_, Mutex<LauncherVariables>>,
+) -> Result<Vec<ScriptStats>, String> {
+    let launcher = launcher.lock().unwrap();
+    let mut stats: Vec<ScriptStats> = launcher.stats.lock().unwrap().values().cloned().collect();
+    stats.sort_by_key(|s| s.id);
+    Ok(stats)
+}
 
-        match status {
-            Ok(Some(exit_status)) => {
-                println!("Process {} exited with status: {}", id, exit_status);
+#[tauri::command]
+pub async fn get_script_progress(
+    launcher: State<'_, Mutex<LauncherVariables>>,
+) -> Result<HashMap<u32, ScriptProgress>, String> {
+    let launcher = launcher.lock().unwrap();
+    let progress = launcher.progress.lock().unwrap().clone();
+    Ok(progress)
+}
 
-                if let Some(launcher_state) = app_clone.try_state::<Mutex<LauncherVariables>>() {
-                    let guard = launcher_state.lock().unwrap();
-                    guard.scripts.lock().unwrap().remove(&id);
-                }
+#[tauri::command]
+pub async fn get_run_history(
+    launcher: State<'_, Mutex<LauncherVariables>>,
+    query: Option<HistoryQuery>,
+) -> Result<Vec<RunRecord>, String> {
+    let run_history = {
+        let launcher = launcher.lock().unwrap();
+        let run_history = launcher.history.lock().unwrap().clone();
+        run_history
+    };
+    Ok(history::query(
+        run_history.load(),
+        &query.unwrap_or_default(),
+    ))
+}
 
-                let _ = app_clone.emit("process-finished", id);
-                break;
-            }
-            Ok(None) => {
-                std::thread::sleep(std::time::Duration::from_millis(500));
-            }
-            Err(e) => {
-                println!("Error checking process status: {}", e);
-                break;
-            }
-        }
-    });
+/// Total runtime per script per day, for the runs matching `query`.
+#[tauri::command]
+pub async fn get_daily_runtime(
+    launcher: State<'_, Mutex<LauncherVariables>>,
+    query: Option<HistoryQuery>,
+) -> Result<Vec<DailyRuntime>, String> {
+    let run_history = {
+        let launcher = launcher.lock().unwrap();
+        let run_history = launcher.history.lock().unwrap().clone();
+        run_history
+    };
+    Ok(history::daily_runtime(
+        run_history.load(),
+        &query.unwrap_or_default(),
+    ))
+}
 
-    Ok("Process started successfully".to_string())
+#[tauri::command]
+pub fn get_max_scripts(launcher: State<'_, Mutex<LauncherVariables>>) -> usize {
+    let launcher = launcher.lock().unwrap();
+    launcher.queue.max()
 }
 
 #[tauri::command]
-pub async fn kill_script(
+pub fn set_max_scripts(
     app: tauri::AppHandle,
-    launcher: tauri::State<'_, Mutex<LauncherVariables>>,
-    id: u32,
-) -> Result<String, String> {
-    let handle = {
-        let launcher_guard = launcher.lock().unwrap();
-        let scripts_guard = launcher_guard.scripts.lock().unwrap();
-        scripts_guard.get(&id).cloned()
-    };
-
-    if let Some(shared_process) = handle {
-        let mut process_guard = shared_process.lock().unwrap();
+    launcher: State<'_, Mutex<LauncherVariables>>,
+    max: usize,
+) {
+    let launcher = launcher.lock().unwrap();
+    launcher.queue.set_max(max);
 
-        if let Some(mut child) = process_guard.take() {
-            let result = child.kill().map_err(|e| e.to_string());
+    app.state::<SettingsStore>()
+        .update(|settings| settings.max_scripts = max);
+}
 
-            let launcher_guard = launcher.lock().unwrap();
-            launcher_guard.scripts.lock().unwrap().remove(&id);
-            let _ = app.emit("process-finished", id);
+#[tauri::command]
+pub fn get_launch_queue(launcher: State<'_, Mutex<LauncherVariables>>) -> Vec<u32> {
+    let launcher = launcher.lock().unwrap();
+    launcher.queue.waiting()
+}
 
-            match result {
-                Ok(_) => Ok(format!("Process {} killed", id)),
-                Err(e) => Err(format!("Failed to kill: {}", e)),
-            }
-        } else {
-            Err(format!("Process {} is already stopping or finished", id))
-        }
+#[tauri::command]
+pub fn cancel_launch(launcher: State<'_, Mutex<LauncherVariables>>, id: u32) -> Result<(), String> {
+    let launcher = launcher.lock().unwrap();
+    if launcher.queue.cancel(id) {
+        Ok(())
     } else {
-        Err(format!("No active script found for ID {}", id))
+        Err(format!("Script {} is not waiting to launch", id))
     }
 }
 
 #[tauri::command]
-pub async fn get_running_scripts() -> Result<(), String> {
+pub fn get_launch_profiles(settings: State<'_, SettingsStore>) -> HashMap<String, LaunchProfile> {
+    settings.get().profiles
+}
+
+#[tauri::command]
+pub fn set_launch_profile(
+    settings: State<'_, SettingsStore>,
+    name: String,
+    profile: Option<LaunchProfile>,
+) -> Result<(), String> {
+    if let Some(profile) = &profile {
+        profile.validate()?;
+    }
+
+    settings.update(|settings| match profile {
+        Some(profile) => {
+            settings.profiles.insert(name, profile);
+        }
+        None => {
+            settings.profiles.remove(&name);
+        }
+    });
     Ok(())
 }
 
@@ -443,38 +494,378 @@ pub async fn reinstall_plugins(
         println!("Deleted folder: {:?}", plugins_path);
     }
 
-    let _ = sync_plugins_repo(&plugins_path).await;
+    sync_plugins_repo(&plugins_path).await
+}
+
+#[tauri::command]
+pub async fn list_clients(
+    settings: State<'_, SettingsStore>,
+    provider: State<'_, Box<dyn ClientProvider>>,
+) -> Result<Vec<WindowMatch>, String> {
+    let settings = settings.get();
+    let matcher = ClientMatcher::new(&settings.client_rules)?;
+    let mut clients = provider.list_clients(&matcher)?;
+
+    for client in &mut clients {
+        client.label = settings.client_labels.get(&client.fingerprint).cloned();
+    }
+
+    Ok(clients)
+}
+
+#[tauri::command]
+pub fn get_client_rules(settings: State<'_, SettingsStore>) -> MatchRules {
+    settings.get().client_rules
+}
+
+#[tauri::command]
+pub fn set_client_rules(
+    settings: State<'_, SettingsStore>,
+    rules: MatchRules,
+) -> Result<(), String> {
+    ClientMatcher::new(&rules)?;
 
+    settings.update(|settings| settings.client_rules = rules);
     Ok(())
 }
 
 #[tauri::command]
-pub async fn list_clients() -> Result<Vec<WindowMatch>, String> {
-    list_processes()
+pub fn set_client_label(
+    settings: State<'_, SettingsStore>,
+    fingerprint: String,
+    label: Option<String>,
+) {
+    settings.update(|settings| match label {
+        Some(label) => {
+            settings.client_labels.insert(fingerprint, label);
+        }
+        None => {
+            settings.client_labels.remove(&fingerprint);
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_client_accounts(settings: State<'_, SettingsStore>) -> HashMap<String, ClientAccount> {
+    settings.get().accounts
+}
+
+#[tauri::command]
+pub fn set_client_account(
+    settings: State<'_, SettingsStore>,
+    name: String,
+    account: Option<ClientAccount>,
+) {
+    settings.update(|settings| match account {
+        Some(account) => {
+            settings.accounts.insert(name, account);
+        }
+        None => {
+            settings.accounts.remove(&name);
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn launch_client(
+    app: tauri::AppHandle,
+    launcher: State<'_, Mutex<LauncherVariables>>,
+    exe: Option<String>,
+    account: Option<String>,
+    slot: Option<String>,
+) -> Result<u32, String> {
+    let settings = match &account {
+        Some(name) => match app.state::<SettingsStore>().get().accounts.remove(name) {
+            Some(account) => account,
+            None => return Err(format!("Unknown client account: {}", name)),
+        },
+        None => ClientAccount::default(),
+    };
+
+    let path = {
+        let launcher = launcher.lock().unwrap();
+        let exe = exe.unwrap_or_else(|| settings.client.clone());
+        let path = match exe.as_str() {
+            "runelite" => launcher.runelite.clone(),
+            "osclient" => launcher.osclient.clone(),
+            _ => {
+                return Err(
+                    "Unrecognized client. Only \"runelite\" or \"osclient\" is allowed."
+                        .to_string(),
+                )
+            }
+        };
+        path
+    };
+
+    let slot = slot
+        .or(account)
+        .unwrap_or_else(|| DEFAULT_CLIENT.to_string());
+    spawn_client(app, path, settings, slot)
+}
+
+#[tauri::command]
+pub fn get_game_clients(launcher: State<'_, Mutex<LauncherVariables>>) -> HashMap<u32, String> {
+    let launcher = launcher.lock().unwrap();
+    let clients = launcher.game_clients.lock().unwrap().clone();
+    clients
+}
+
+#[tauri::command]
+pub fn get_layouts(settings: State<'_, SettingsStore>) -> HashMap<String, WindowLayout> {
+    settings.get().layouts
+}
+
+#[tauri::command]
+pub fn set_layout(settings: State<'_, SettingsStore>, name: String, layout: Option<WindowLayout>) {
+    settings.update(|settings| match layout {
+        Some(layout) => {
+            settings.layouts.insert(name, layout);
+        }
+        None => {
+            settings.layouts.remove(&name);
+        }
+    });
+}
+
+#[tauri::command]
+pub fn save_current_layout(
+    settings: State<'_, SettingsStore>,
+    launcher: State<'_, Mutex<LauncherVariables>>,
+    provider: State<'_, Box<dyn ClientProvider>>,
+    name: String,
+) -> Result<WindowLayout, String> {
+    let clients = {
+        let launcher = launcher.lock().unwrap();
+        launcher.clients.clone()
+    };
+
+    let windows: HashMap<String, WindowGeometry> = clients
+        .iter()
+        .filter_map(|(slot, client)| Some((slot.clone(), provider.frame_geometry(client)?)))
+        .collect();
+
+    if windows.is_empty() {
+        return Err("No selected client windows to save.".to_string());
+    }
+
+    let layout = WindowLayout::Custom { windows };
+    set_layout(settings, name, Some(layout.clone()));
+    Ok(layout)
+}
+
+#[tauri::command]
+pub async fn apply_layout(
+    settings: State<'_, SettingsStore>,
+    launcher: State<'_, Mutex<LauncherVariables>>,
+    provider: State<'_, Box<dyn ClientProvider>>,
+    name: String,
+) -> Result<(), String> {
+    let Some(layout) = settings.get().layouts.remove(&name) else {
+        return Err(format!("Unknown layout: {}", name));
+    };
+    let clients = {
+        let launcher = launcher.lock().unwrap();
+        launcher.clients.clone()
+    };
+
+    let slots: Vec<String> = clients.keys().cloned().collect();
+    let failed: Vec<String> = layout
+        .arrange(&slots)
+        .into_iter()
+        .filter(|(slot, geometry)| !provider.move_client(&clients[slot], *geometry))
+        .map(|(slot, _)| slot)
+        .collect();
+
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Failed to move clients: {}", failed.join(", ")))
+    }
+}
+
+#[tauri::command]
+pub async fn get_clients(
+    launcher: State<'_, Mutex<LauncherVariables>>,
+) -> Result<HashMap<String, WindowMatch>, String> {
+    let launcher = launcher.lock().unwrap();
+    Ok(launcher.clients.clone())
 }
 
 #[tauri::command]
 pub async fn set_client(
     launcher: State<'_, Mutex<LauncherVariables>>,
+    provider: State<'_, Box<dyn ClientProvider>>,
+    id: Option<String>,
     client: Option<WindowMatch>,
-) -> tauri::Result<()> {
+) -> Result<(), String> {
+    let id = id.unwrap_or_else(|| DEFAULT_CLIENT.to_string());
+
+    if let Some(client) = &client {
+        if !provider.is_alive(client) {
+            return Err("Client is no longer running.".to_string());
+        }
+    }
+
     let mut launcher = launcher.lock().unwrap();
-    launcher.client = client;
+    match client {
+        Some(client) => launcher.clients.insert(id, client),
+        None => launcher.clients.remove(&id),
+    };
     Ok(())
 }
 
 #[tauri::command]
-pub async fn show_client(launcher: State<'_, Mutex<LauncherVariables>>) -> Result<(), String> {
-    let launcher = launcher.lock().unwrap();
-    match &launcher.client {
+pub async fn show_client(
+    launcher: State<'_, Mutex<LauncherVariables>>,
+    provider: State<'_, Box<dyn ClientProvider>>,
+    id: Option<String>,
+) -> Result<(), String> {
+    let id = id.unwrap_or_else(|| DEFAULT_CLIENT.to_string());
+    let client = {
+        let launcher = launcher.lock().unwrap();
+        launcher.clients.get(&id).cloned()
+    };
+
+    match client {
         Some(client) => {
-            let hwnd = client.hwnd;
-            if bring_window_to_top(hwnd) {
+            if provider.focus_client(&client) {
                 Ok(())
             } else {
                 Err("Failed to bring window to front. The handle might be invalid.".to_string())
             }
         }
-        None => Err("Client is null".to_string()),
+        None => Err(format!("Client {} is null", id)),
     }
 }
+
+#[tauri::command]
+pub fn export_settings(app: tauri::AppHandle, path: PathBuf) -> Result<(), String> {
+    let data = app.state::<SettingsStore>().export()?;
+    write(&path, data).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn read_bundle(path: &Path) -> Result<String, String> {
+    read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+}
+
+#[tauri::command]
+pub fn preview_settings_import(
+    app: tauri::AppHandle,
+    path: PathBuf,
+) -> Result<Vec<SettingChange>, String> {
+    let data = read_bundle(&path)?;
+    let (_, changes) = app.state::<SettingsStore>().preview_import(&data)?;
+    Ok(changes)
+}
+
+#[tauri::command]
+pub fn import_settings(
+    app: tauri::AppHandle,
+    launcher: State<'_, Mutex<LauncherVariables>>,
+    path: PathBuf,
+) -> Result<Vec<SettingChange>, String> {
+    let data = read_bundle(&path)?;
+    let settings = app.state::<SettingsStore>();
+    let (incoming, changes) = settings.preview_import(&data)?;
+
+    launcher.lock().unwrap().queue.set_max(incoming.max_scripts);
+    settings.update(|settings| *settings = incoming);
+    Ok(changes)
+}
+
+#[tauri::command]
+pub fn get_control_settings(app: tauri::AppHandle) -> ControlSettings {
+    app.state::<SettingsStore>().get().control
+}
+
+/// Takes effect on the next launch. An empty token gets a freshly generated one.
+#[tauri::command]
+pub fn set_control_settings(
+    app: tauri::AppHandle,
+    mut control: ControlSettings,
+) -> ControlSettings {
+    if control.token.is_empty() {
+        control.token = generate_token();
+    }
+
+    let saved = control.clone();
+    app.state::<SettingsStore>()
+        .update(|settings| settings.control = control);
+    saved
+}
+
+#[tauri::command]
+pub fn get_metrics_settings(app: tauri::AppHandle) -> MetricsSettings {
+    app.state::<SettingsStore>().get().metrics
+}
+
+/// Takes effect on the next launch.
+#[tauri::command]
+pub fn set_metrics_settings(app: tauri::AppHandle, metrics: MetricsSettings) {
+    app.state::<SettingsStore>()
+        .update(|settings| settings.metrics = metrics);
+}
+
+#[tauri::command]
+pub fn get_webhooks(app: tauri::AppHandle) -> Vec<Webhook> {
+    app.state::<SettingsStore>().get().webhooks
+}
+
+#[tauri::command]
+pub fn set_webhooks(app: tauri::AppHandle, webhooks: Vec<Webhook>) {
+    app.state::<SettingsStore>()
+        .update(|settings| settings.webhooks = webhooks);
+}
+
+#[tauri::command]
+pub async fn send_test_webhook(app: tauri::AppHandle, name: String) -> Result<(), String> {
+    let webhook = app
+        .state::<SettingsStore>()
+        .get()
+        .webhooks
+        .into_iter()
+        .find(|webhook| webhook.name == name)
+        .ok_or_else(|| format!("Unknown webhook: {}", name))?;
+
+    test_webhook(&webhook).await
+}
+
+#[tauri::command]
+pub fn get_triggers(app: tauri::AppHandle) -> Vec<TriggerRule> {
+    app.state::<SettingsStore>().get().triggers
+}
+
+#[tauri::command]
+pub fn set_triggers(app: tauri::AppHandle, triggers: Vec<TriggerRule>) -> Result<(), String> {
+    for rule in &triggers {
+        Trigger::new(rule.clone())?;
+    }
+    app.state::<SettingsStore>()
+        .update(|settings| settings.triggers = triggers);
+    Ok(())
+}
+
+/// Shows which lines of `lines` would fire which rules for `script_id`, using the saved
+/// rules unless `triggers` is given.
+#[tauri::command]
+pub fn test_triggers(
+    app: tauri::AppHandle,
+    lines: Vec<String>,
+    script_id: Option<String>,
+    triggers: Option<Vec<TriggerRule>>,
+) -> Result<Vec<TriggerMatch>, String> {
+    let rules = triggers.unwrap_or_else(|| app.state::<SettingsStore>().get().triggers);
+    dry_run(&rules, script_id.as_deref().unwrap_or(""), &lines)
+}
+
+#[tauri::command]
+pub fn get_notifications(app: tauri::AppHandle) -> bool {
+    app.state::<SettingsStore>().get().notifications
+}
+
+#[tauri::command]
+pub fn set_notifications(app: tauri::AppHandle, state: bool) {
+    app.state::<SettingsStore>()
+        .update(|settings| settings.notifications = state);
+}
@@ -0,0 +1,455 @@
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    path::PathBuf,
+    sync::Mutex,
+    thread,
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{ipc::Channel, Manager};
+
+use crate::{
+    client::WindowMatch,
+    commands,
+    scripts::{stop_script, ExitReason, ScriptRecord},
+    LauncherVariables,
+};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const SERVER_ERROR: i64 = -32000;
+const UNAUTHORIZED: i64 = -32001;
+
+const DEFAULT_TAIL_LINES: usize = 100;
+// Longest request line we buffer, the connection is dropped past that.
+const MAX_LINE_LENGTH: u64 = 1024 * 1024;
+
+/// Opt-in JSON-RPC server for external tooling. Changes apply on the next start.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ControlSettings {
+    pub enabled: bool,
+    pub port: u16,
+    /// Listen on this Unix domain socket instead of the port. Ignored on Windows.
+    pub socket: Option<PathBuf>,
+    /// Every connection has to `auth` with this before calling anything else.
+    pub token: String,
+}
+
+impl Default for ControlSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 5218,
+            socket: None,
+            token: String::new(),
+        }
+    }
+}
+
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    getrandom::fill(&mut bytes).expect("OS random number generator is unavailable");
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Compares without returning early, so response times don't leak how much of the
+/// token a guess got right.
+fn token_matches(given: &str, token: &str) -> bool {
+    let (given, token) = (given.as_bytes(), token.as_bytes());
+    given.len() == token.len()
+        && given
+            .iter()
+            .zip(token)
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[derive(Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl From<String> for RpcError {
+    fn from(message: String) -> Self {
+        Self {
+            code: SERVER_ERROR,
+            message,
+        }
+    }
+}
+
+fn response(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(e) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": e.code, "message": e.message }
+        }),
+    }
+}
+
+fn params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    let params = if params.is_null() { json!({}) } else { params };
+    serde_json::from_value(params).map_err(|e| RpcError {
+        code: INVALID_PARAMS,
+        message: e.to_string(),
+    })
+}
+
+fn to_value<T: Serialize>(value: T) -> Result<Value, RpcError> {
+    serde_json::to_value(value).map_err(|e| RpcError::from(e.to_string()))
+}
+
+#[derive(Deserialize)]
+struct AuthParams {
+    token: String,
+}
+
+#[derive(Deserialize)]
+struct RunParams {
+    args: Vec<String>,
+    client: Option<String>,
+    profile: Option<String>,
+    force: Option<bool>,
+}
+
+#[derive(Deserialize)]
+struct ScriptParams {
+    id: u32,
+}
+
+#[derive(Deserialize)]
+struct TailParams {
+    id: u32,
+    lines: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct SetClientParams {
+    id: Option<String>,
+    client: Option<WindowMatch>,
+}
+
+fn running_script(app: &tauri::AppHandle, id: u32) -> Result<ScriptRecord, RpcError> {
+    let launcher = app.state::<Mutex<LauncherVariables>>();
+    let launcher = launcher.lock().unwrap();
+    let records = launcher.registry.lock().unwrap().records();
+    records
+        .into_iter()
+        .find(|record| record.id == id)
+        .ok_or_else(|| RpcError::from(format!("No active script found for ID {}", id)))
+}
+
+async fn call(app: &tauri::AppHandle, method: &str, raw: Value) -> Result<Value, RpcError> {
+    match method {
+        "run_script" => {
+            let p: RunParams = params(raw)?;
+            // Output already goes to the script's log file, which `tail_log` reads.
+            let channel: Channel<String> = Channel::new(|_| Ok(()));
+            let id =
+                commands::run_script(app.clone(), p.args, p.client, p.profile, p.force, channel)
+                    .await?;
+            Ok(json!({ "id": id }))
+        }
+        "stop_script" => {
+            let p: ScriptParams = params(raw)?;
+            to_value(stop_script(app, p.id, ExitReason::Killed)?)
+        }
+        "list_scripts" => to_value(commands::get_running_scripts(app.state()).await?),
+        "script_stats" => to_value(commands::get_script_stats(app.state()).await?),
+        "script_progress" => to_value(commands::get_script_progress(app.state()).await?),
+        "tail_log" => {
+            let p: TailParams = params(raw)?;
+            let record = running_script(app, p.id)?;
+            let log = fs::read_to_string(&record.log_path).map_err(|e| e.to_string())?;
+            let lines: Vec<&str> = log.lines().collect();
+            let count = p.lines.unwrap_or(DEFAULT_TAIL_LINES).min(lines.len());
+            to_value(&lines[lines.len() - count..])
+        }
+        "list_clients" => to_value(commands::list_clients(app.state(), app.state()).await?),
+        "get_clients" => to_value(commands::get_clients(app.state()).await?),
+        "set_client" => {
+            let p: SetClientParams = params(raw)?;
+            commands::set_client(app.state(), app.state(), p.id, p.client).await?;
+            Ok(Value::Null)
+        }
+        _ => Err(RpcError {
+            code: METHOD_NOT_FOUND,
+            message: format!("Unknown method: {}", method),
+        }),
+    }
+}
+
+/// Handles one request line. Returns the response and whether the connection is
+/// authenticated afterwards.
+fn handle_line(app: &tauri::AppHandle, token: &str, authed: bool, line: &str) -> (Value, bool) {
+    let request: Request = match serde_json::from_str::<Value>(line) {
+        Ok(value) => match serde_json::from_value(value) {
+            Ok(request) => request,
+            Err(e) => {
+                let e = RpcError {
+                    code: INVALID_REQUEST,
+                    message: e.to_string(),
+                };
+                return (response(Value::Null, Err(e)), authed);
+            }
+        },
+        Err(e) => {
+            let e = RpcError {
+                code: PARSE_ERROR,
+                message: e.to_string(),
+            };
+            return (response(Value::Null, Err(e)), authed);
+        }
+    };
+
+    if request.method == "auth" {
+        let result = params::<AuthParams>(request.params).and_then(|p| {
+            if token_matches(&p.token, token) {
+                Ok(Value::Bool(true))
+            } else {
+                Err(RpcError {
+                    code: UNAUTHORIZED,
+                    message: "Invalid token".to_string(),
+                })
+            }
+        });
+        let authed = result.is_ok();
+        return (response(request.id, result), authed);
+    }
+
+    if !authed {
+        let e = RpcError {
+            code: UNAUTHORIZED,
+            message: "Call auth with the control token first".to_string(),
+        };
+        return (response(request.id, Err(e)), false);
+    }
+
+    let result = tauri::async_runtime::block_on(call(app, &request.method, request.params));
+    (response(request.id, result), true)
+}
+
+/// Reads one request line, `None` once the peer closed the connection. Lines longer
+/// than `MAX_LINE_LENGTH` are an error.
+fn read_line<R: BufRead>(reader: &mut R) -> io::Result<Option<String>> {
+    let mut line = Vec::new();
+    let read = reader
+        .take(MAX_LINE_LENGTH + 1)
+        .read_until(b'\n', &mut line)?;
+    if read == 0 {
+        return Ok(None);
+    }
+    if read as u64 > MAX_LINE_LENGTH {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Request longer than {} bytes", MAX_LINE_LENGTH),
+        ));
+    }
+
+    String::from_utf8(line)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Serves newline delimited JSON-RPC 2.0 requests until the peer disconnects.
+fn serve<R: BufRead, W: Write>(app: tauri::AppHandle, token: String, mut reader: R, mut writer: W) {
+    let mut authed = false;
+
+    loop {
+        let line = match read_line(&mut reader) {
+            Ok(Some(line)) => line,
+            Ok(None) => break,
+            Err(e) => {
+                let e = RpcError {
+                    code: INVALID_REQUEST,
+                    message: e.to_string(),
+                };
+                let _ = writeln!(writer, "{}", response(Value::Null, Err(e)));
+                break;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (reply, now_authed) = handle_line(&app, &token, authed, &line);
+        authed = now_authed;
+
+        if writeln!(writer, "{}", reply).is_err() || writer.flush().is_err() {
+            break;
+        }
+    }
+}
+
+fn accept<S: Read + Write + Send + 'static>(
+    app: &tauri::AppHandle,
+    token: &str,
+    streams: io::Result<(S, S)>,
+) {
+    match streams {
+        Ok((reader, writer)) => {
+            let (app, token) = (app.clone(), token.to_string());
+            thread::spawn(move || serve(app, token, BufReader::new(reader), writer));
+        }
+        Err(e) => eprintln!("Error: {}", e),
+    }
+}
+
+#[cfg(unix)]
+fn listen_socket(app: tauri::AppHandle, token: String, path: PathBuf) -> io::Result<()> {
+    use std::os::unix::{fs::PermissionsExt, net::UnixListener};
+
+    remove_stale_socket(&path)?;
+    let listener = UnixListener::bind(&path)?;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+    println!("Control server listening on {}", path.display());
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            accept(&app, &token, stream.and_then(|s| Ok((s.try_clone()?, s))));
+        }
+    });
+    Ok(())
+}
+
+/// Removes a socket left behind by a previous instance, which would make bind fail.
+/// Anything that isn't a socket, or a socket something still listens on, is kept.
+#[cfg(unix)]
+fn remove_stale_socket(path: &std::path::Path) -> io::Result<()> {
+    use std::os::unix::{fs::FileTypeExt, net::UnixStream};
+
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    if !metadata.file_type().is_socket() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} exists and is not a socket", path.display()),
+        ));
+    }
+    if UnixStream::connect(path).is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            format!("{} is in use by another process", path.display()),
+        ));
+    }
+
+    fs::remove_file(path)
+}
+
+#[cfg(not(unix))]
+fn listen_socket(_app: tauri::AppHandle, _token: String, _path: PathBuf) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Unix domain sockets are not supported on this platform",
+    ))
+}
+
+fn listen_port(app: tauri::AppHandle, token: String, port: u16) -> io::Result<()> {
+    let listener = TcpListener::bind(("127.0.0.1", port))?;
+    println!("Control server listening on localhost:{}", port);
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            accept(&app, &token, stream.and_then(|s| Ok((s.try_clone()?, s))));
+        }
+    });
+    Ok(())
+}
+
+pub fn start_control_server(app: tauri::AppHandle, settings: ControlSettings) {
+    if !settings.enabled {
+        return;
+    }
+
+    if settings.token.is_empty() {
+        eprintln!("Control server is enabled without a token, not starting it.");
+        return;
+    }
+
+    let result = match settings.socket.filter(|_| cfg!(unix)) {
+        Some(path) => listen_socket(app, settings.token, path),
+        None => listen_port(app, settings.token, settings.port),
+    };
+
+    if let Err(e) = result {
+        eprintln!("Failed to start control server: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn generates_distinct_tokens() {
+        let token = generate_token();
+        assert_eq!(token.len(), 64);
+        assert!(token.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(token, generate_token());
+    }
+
+    #[test]
+    fn matches_only_the_exact_token() {
+        assert!(token_matches("secret", "secret"));
+        assert!(!token_matches("secreT", "secret"));
+        assert!(!token_matches("secret!", "secret"));
+        assert!(!token_matches("", "secret"));
+    }
+
+    #[test]
+    fn rejects_oversized_lines() {
+        let mut reader = Cursor::new(b"{\"method\":\"auth\"}\nlast".to_vec());
+        assert_eq!(
+            read_line(&mut reader).unwrap().as_deref(),
+            Some("{\"method\":\"auth\"}\n")
+        );
+        assert_eq!(read_line(&mut reader).unwrap().as_deref(), Some("last"));
+        assert!(read_line(&mut reader).unwrap().is_none());
+
+        let long = vec![b'a'; MAX_LINE_LENGTH as usize + 1];
+        let err = read_line(&mut Cursor::new(long)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn only_removes_stale_sockets() {
+        use std::os::unix::net::UnixListener;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("control.sock");
+        assert!(remove_stale_socket(&path).is_ok());
+
+        let listener = UnixListener::bind(&path).unwrap();
+        assert!(remove_stale_socket(&path).is_err());
+        drop(listener);
+        remove_stale_socket(&path).unwrap();
+        assert!(!path.exists());
+
+        fs::write(&path, "not a socket").unwrap();
+        assert!(remove_stale_socket(&path).is_err());
+        assert!(path.exists());
+    }
+}
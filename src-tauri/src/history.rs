@@ -0,0 +1,323 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, create_dir_all, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    progress::ScriptProgress,
+    scripts::{unix_now, ExitReason, LaunchRequest, ScriptExit, ScriptRecord},
+};
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// One finished script run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub id: u32,
+    pub script_id: String,
+    /// Empty for scripts re-adopted after a launcher restart, which don't keep their
+    /// launch arguments.
+    pub revision: String,
+    /// Versions the script ran with, with `latest` already resolved.
+    pub simba: String,
+    pub wasplib: String,
+    pub client: Option<String>,
+    pub started: u64,
+    pub ended: u64,
+    pub code: Option<i32>,
+    pub reason: ExitReason,
+    pub restarts: u32,
+    pub log_path: PathBuf,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub progress: Option<ScriptProgress>,
+}
+
+impl RunRecord {
+    pub fn new(
+        record: &ScriptRecord,
+        request: Option<&LaunchRequest>,
+        progress: Option<ScriptProgress>,
+        exit: ScriptExit,
+    ) -> Self {
+        let arg = |index: usize| {
+            request
+                .and_then(|r| r.args.get(index).cloned())
+                .unwrap_or_default()
+        };
+
+        Self {
+            id: record.id,
+            script_id: record.script_id.clone(),
+            revision: arg(4),
+            simba: record.simba.clone(),
+            wasplib: record.wasplib.clone(),
+            client: record.client.as_ref().map(|c| c.display_name()),
+            started: record.started,
+            ended: unix_now().max(record.started),
+            code: exit.code,
+            reason: exit.reason,
+            restarts: request.map(|r| r.restarts).unwrap_or(0),
+            log_path: record.log_path.clone(),
+            tags: record.tags.clone(),
+            progress,
+        }
+    }
+}
+
+/// Append-only history of finished runs, one JSON object per line.
+#[derive(Debug, Clone, Default)]
+pub struct RunHistory {
+    path: PathBuf,
+}
+
+impl RunHistory {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn append(&self, run: &RunRecord) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            create_dir_all(parent)?;
+        }
+
+        let line = serde_json::to_string(run)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", line)
+    }
+
+    /// Every run in the order they finished. Lines that fail to parse, e.g. one cut
+    /// short by a crash, are skipped.
+    pub fn load(&self) -> Vec<RunRecord> {
+        fs::read_to_string(&self.path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct HistoryQuery {
+    pub script_id: Option<String>,
+    /// Unix time, runs that ended before it are left out.
+    pub since: Option<u64>,
+    /// Unix time, runs that started after it are left out.
+    pub until: Option<u64>,
+    /// Newest runs to return, all of them when unset.
+    pub limit: Option<usize>,
+    /// Seconds east of UTC that days are counted in.
+    pub utc_offset: i64,
+}
+
+impl HistoryQuery {
+    fn matches(&self, run: &RunRecord) -> bool {
+        self.script_id
+            .as_ref()
+            .is_none_or(|id| &run.script_id == id)
+            && self.since.is_none_or(|since| run.ended >= since)
+            && self.until.is_none_or(|until| run.started <= until)
+    }
+}
+
+/// Runs matching `query`, newest first.
+pub fn query(runs: Vec<RunRecord>, query: &HistoryQuery) -> Vec<RunRecord> {
+    let mut runs: Vec<RunRecord> = runs.into_iter().filter(|r| query.matches(r)).collect();
+    runs.sort_by_key(|r| std::cmp::Reverse(r.ended));
+    if let Some(limit) = query.limit {
+        runs.truncate(limit);
+    }
+    runs
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyRuntime {
+    /// `YYYY-MM-DD`, in the query's UTC offset.
+    pub day: String,
+    pub script_id: String,
+    /// Seconds the script ran that day.
+    pub runtime: u64,
+    /// Runs that were active that day.
+    pub runs: u32,
+}
+
+// Days since 1970-01-01 to a civil date, from Howard Hinnant's `civil_from_days`.
+fn format_day(days: i64) -> String {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Total runtime per script per day. Runs crossing midnight count towards both days.
+pub fn daily_runtime(runs: Vec<RunRecord>, query: &HistoryQuery) -> Vec<DailyRuntime> {
+    let mut totals: BTreeMap<(i64, String), (u64, u32)> = BTreeMap::new();
+
+    for run in runs.into_iter().filter(|r| query.matches(r)) {
+        let start = run.started as i64 + query.utc_offset;
+        let end = run.ended as i64 + query.utc_offset;
+        let mut at = start;
+
+        loop {
+            let day = at.div_euclid(DAY as i64);
+            let next = (day + 1) * DAY as i64;
+            let until = end.min(next);
+
+            let total = totals.entry((day, run.script_id.clone())).or_default();
+            total.0 += (until - at) as u64;
+            total.1 += 1;
+
+            if until >= end {
+                break;
+            }
+            at = until;
+        }
+    }
+
+    totals
+        .into_iter()
+        .map(|((day, script_id), (runtime, runs))| DailyRuntime {
+            day: format_day(day),
+            script_id,
+            runtime,
+            runs,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-01-01 00:00:00 UTC.
+    const NEW_YEAR: u64 = 1_704_067_200;
+
+    fn run(script_id: &str, started: u64, ended: u64) -> RunRecord {
+        RunRecord {
+            id: 1,
+            script_id: script_id.to_string(),
+            revision: String::new(),
+            simba: String::new(),
+            wasplib: String::new(),
+            client: None,
+            started,
+            ended,
+            code: Some(0),
+            reason: ExitReason::Exited,
+            restarts: 0,
+            log_path: PathBuf::from("1.log"),
+            tags: Vec::new(),
+            progress: None,
+        }
+    }
+
+    fn days(totals: &[DailyRuntime]) -> Vec<(&str, u64, u32)> {
+        totals
+            .iter()
+            .map(|t| (t.day.as_str(), t.runtime, t.runs))
+            .collect()
+    }
+
+    #[test]
+    fn formats_days_since_the_epoch() {
+        assert_eq!(format_day(0), "1970-01-01");
+        assert_eq!(format_day(-1), "1969-12-31");
+        assert_eq!(format_day((NEW_YEAR / DAY) as i64), "2024-01-01");
+        assert_eq!(format_day((NEW_YEAR / DAY) as i64 + 59), "2024-02-29");
+    }
+
+    #[test]
+    fn splits_runs_crossing_midnight() {
+        let runs = vec![run("a", NEW_YEAR - 600, NEW_YEAR + 300)];
+        let totals = daily_runtime(runs, &HistoryQuery::default());
+        assert_eq!(
+            days(&totals),
+            vec![("2023-12-31", 600, 1), ("2024-01-01", 300, 1)]
+        );
+    }
+
+    #[test]
+    fn counts_days_in_the_utc_offset() {
+        // 00:30 to 01:00 UTC is 23:30 to 00:00 an hour west of it.
+        let runs = vec![run("a", NEW_YEAR + 1800, NEW_YEAR + 3600)];
+        let query = HistoryQuery {
+            utc_offset: -3600,
+            ..Default::default()
+        };
+        assert_eq!(
+            days(&daily_runtime(runs, &query)),
+            vec![("2023-12-31", 1800, 1)]
+        );
+    }
+
+    #[test]
+    fn query_filters_sorts_and_limits() {
+        let runs = vec![
+            run("a", 100, 200),
+            run("b", 150, 250),
+            run("a", 300, 400),
+            run("a", 500, 600),
+        ];
+
+        let query_for = |script_id: &str| HistoryQuery {
+            script_id: Some(script_id.to_string()),
+            ..Default::default()
+        };
+        let ended: Vec<u64> = query(runs.clone(), &query_for("a"))
+            .iter()
+            .map(|r| r.ended)
+            .collect();
+        assert_eq!(ended, vec![600, 400, 200]);
+
+        let window = HistoryQuery {
+            since: Some(250),
+            until: Some(450),
+            ..Default::default()
+        };
+        let ended: Vec<u64> = query(runs.clone(), &window)
+            .iter()
+            .map(|r| r.ended)
+            .collect();
+        assert_eq!(ended, vec![400, 250]);
+
+        let newest = HistoryQuery {
+            limit: Some(2),
+            ..Default::default()
+        };
+        let ended: Vec<u64> = query(runs, &newest).iter().map(|r| r.ended).collect();
+        assert_eq!(ended, vec![600, 400]);
+    }
+
+    #[test]
+    fn load_skips_broken_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history").join("runs.jsonl");
+        let history = RunHistory::new(path.clone());
+
+        history.append(&run("a", 100, 200)).unwrap();
+        fs::write(
+            &path,
+            fs::read_to_string(&path).unwrap() + "{\"id\": 2, \"scr\n",
+        )
+        .unwrap();
+        history.append(&run("b", 300, 400)).unwrap();
+
+        let ids: Vec<String> = history.load().into_iter().map(|r| r.script_id).collect();
+        assert_eq!(ids, vec!["a", "b"]);
+    }
+}
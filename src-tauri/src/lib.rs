@@ -1,33 +1,71 @@
+mod cli;
 mod client;
 mod commands;
+mod control;
+mod history;
+mod metrics;
+mod output;
+mod process;
+mod profiles;
+mod progress;
+mod queue;
+mod scripts;
 mod server;
+mod settings;
 mod simba;
+mod stats;
+mod tray;
+mod triggers;
+mod watchdog;
+mod webhooks;
 
 use std::{
     collections::HashMap,
-    env,
     path::PathBuf,
-    process::Child,
     sync::{Arc, Mutex},
 };
 
-use serde_json::json;
-use tauri::Manager;
+use tauri::{Manager, WebviewWindowBuilder};
 use tauri_plugin_store::StoreExt;
 
 use tauri_plugin_cli::CliExt;
 use tauri_plugin_updater::UpdaterExt;
 
-use crate::client::WindowMatch;
+use crate::{
+    client::{platform_provider, start_client_watcher, WindowMatch},
+    history::RunHistory,
+    progress::ScriptProgress,
+    queue::LaunchQueue,
+    scripts::{LaunchRequest, ScriptRegistry, SharedProcess},
+    settings::SettingsStore,
+    stats::ScriptStats,
+    webhooks::WebhookDispatcher,
+};
+
+const DEFAULT_OSCLIENT_PATH: &str =
+    r"C:\Program Files (x86)\Jagex Launcher\Games\Old School RuneScape\Client\osclient.exe";
 
 #[derive(Default)]
 struct LauncherVariables {
-    devmode: bool,
     simba: PathBuf,
     devsimba: PathBuf,
-    client: Option<WindowMatch>,
-    dev_updates: bool,
-    scripts: Mutex<HashMap<u32, Arc<Mutex<Option<Child>>>>>,
+    runelite: PathBuf,
+    osclient: PathBuf,
+    clients: HashMap<String, WindowMatch>,
+    game_clients: Mutex<HashMap<u32, String>>,
+    logs: PathBuf,
+    scripts: Mutex<HashMap<u32, SharedProcess>>,
+    launches: Mutex<HashMap<u32, LaunchRequest>>,
+    registry: Mutex<ScriptRegistry>,
+    stats: Mutex<HashMap<u32, ScriptStats>>,
+    progress: Mutex<HashMap<u32, ScriptProgress>>,
+    history: Mutex<RunHistory>,
+    queue: Arc<LaunchQueue>,
+    bindings: Mutex<HashMap<u32, WindowMatch>>,
+    /// Scripts waiting to be launched again, and whether that was cancelled meanwhile.
+    restarting: Mutex<HashMap<u32, bool>>,
+    /// Started for a CLI subcommand, without a window or tray.
+    headless: bool,
 }
 
 async fn update_launcher(app: tauri::AppHandle) -> tauri_plugin_updater::Result<()> {
@@ -62,83 +100,115 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_store::Builder::default().build())
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_notification::init())
         .setup(|app| {
-            let window = app.get_webview_window("main").unwrap();
-            match app.cli().matches() {
-                Ok(matches) => {
-                    if let Some(arg) = matches.args.get("debug") {
-                        if arg.occurrences > 0 {
-                            println!("Debug flag present!");
-                            window.open_devtools();
-                        }
+            let mut matches = app.cli().matches().ok();
+            let headless = matches.as_mut().and_then(|m| m.subcommand.take());
+
+            // Subcommands run headless, the main window is only created for the GUI.
+            let window = match headless {
+                Some(_) => None,
+                None => {
+                    let config = app
+                        .config()
+                        .app
+                        .windows
+                        .iter()
+                        .find(|w| w.label == "main")
+                        .expect("Main window missing from tauri.conf.json");
+                    Some(WebviewWindowBuilder::from_config(app.handle(), config)?.build()?)
+                }
+            };
+
+            if let (Some(window), Some(matches)) = (&window, &matches) {
+                if let Some(arg) = matches.args.get("debug") {
+                    if arg.occurrences > 0 {
+                        println!("Debug flag present!");
+                        window.open_devtools();
                     }
                 }
-                Err(_) => {}
             }
 
             let handle = app.handle().clone();
-            if !tauri::is_dev() {
+            if headless.is_none() && !tauri::is_dev() {
                 tauri::async_runtime::spawn(async move {
                     update_launcher(handle).await.unwrap();
                 });
             }
 
-            let settings = app.store("settings.json")?;
-
             let app_paths = app.path();
+            let settings = SettingsStore::load(
+                app.store("settings.json")?,
+                &app_paths.app_data_dir()?.join("settings.json"),
+            );
+            let current = settings.get();
+
             let local_data = app_paths
                 .app_local_data_dir()
                 .expect("Local Data Dir doesn't exist on this system");
 
-            let paths: serde_json::Value = settings.get("paths").unwrap_or_else(|| {
-                let empty = json!({});
-                settings.set("paths", empty.clone());
-                empty
-            });
-
-            let get_path = |key: &str, fallback: PathBuf| {
-                paths
-                    .get(key)
-                    .and_then(|v| v.as_str())
-                    .map(PathBuf::from)
-                    .unwrap_or(fallback)
-            };
-
             let simba_path = local_data.join("Simba");
+            let runelite_path = app_paths
+                .local_data_dir()
+                .map(|dir| dir.join("RuneLite").join("RuneLite.exe"))
+                .unwrap_or_default();
             let _ = simba::ensure_simba_directories(&simba_path);
 
-            let plugins_path = simba_path.join("Plugins").join("wasp-plugins");
-            tauri::async_runtime::spawn(async move {
-                println!("Started plugins async thread!");
-                let _ = simba::sync_plugins_repo(&plugins_path).await;
-            });
+            if headless.is_none() {
+                let plugins_path = simba_path.join("Plugins").join("wasp-plugins");
+                tauri::async_runtime::spawn(async move {
+                    println!("Started plugins async thread!");
+                    if let Err(e) = simba::sync_plugins_repo(&plugins_path).await {
+                        eprintln!("{}", e);
+                    }
+                });
+            }
 
-            let devmode: bool = match settings.get("devmode") {
-                Some(value) => value.as_bool().unwrap_or(false),
-                None => {
-                    settings.set("devmode", false);
-                    false
-                }
-            };
+            app.manage(platform_provider());
 
-            let dev_updates: bool = match settings.get("dev_updates") {
-                Some(value) => value.as_bool().unwrap_or(true),
-                None => {
-                    settings.set("dev_updates", true);
-                    true
-                }
-            };
+            app.manage(settings);
+            app.manage(WebhookDispatcher::default());
 
             app.manage(Mutex::new(LauncherVariables {
                 simba: simba_path.clone(),
-                devmode: devmode,
-                devsimba: get_path("devsimba", simba_path),
-                client: None,
-                dev_updates: dev_updates,
+                devsimba: current.path("devsimba").unwrap_or(simba_path),
+                runelite: current.path("runelite").unwrap_or(runelite_path),
+                osclient: current
+                    .path("osclient")
+                    .unwrap_or_else(|| PathBuf::from(DEFAULT_OSCLIENT_PATH)),
+                clients: HashMap::new(),
+                game_clients: Mutex::new(HashMap::new()),
+                logs: local_data.join("Logs"),
                 scripts: Mutex::new(HashMap::new()),
+                launches: Mutex::new(HashMap::new()),
+                registry: Mutex::new(ScriptRegistry::load(local_data.join("scripts.json"))),
+                stats: Mutex::new(HashMap::new()),
+                progress: Mutex::new(HashMap::new()),
+                history: Mutex::new(RunHistory::new(local_data.join("history.jsonl"))),
+                queue: Arc::new(
+                    LaunchQueue::new(current.max_scripts)
+                        .with_listener(queue::position_events(app.handle().clone())),
+                ),
+                bindings: Mutex::new(HashMap::new()),
+                restarting: Mutex::new(HashMap::new()),
+                headless: headless.is_some(),
             }));
 
-            let _ = window.set_background_color(Some([25, 25, 25].into()));
+            if let Some(command) = headless {
+                cli::run_headless(app.handle().clone(), *command);
+                return Ok(());
+            }
+
+            scripts::readopt_scripts(app.handle());
+            stats::start_stats_sampler(app.handle().clone());
+            start_client_watcher(app.handle().clone());
+            control::start_control_server(app.handle().clone(), current.control);
+            metrics::start_metrics_server(app.handle().clone(), current.metrics);
+
+            if let Some(window) = window {
+                tray::create_tray(app.handle(), &window);
+                let _ = window.set_background_color(Some([25, 25, 25].into()));
+            }
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -160,9 +230,48 @@ pub fn run() {
             commands::get_plugin_version,
             commands::reinstall_plugins,
             commands::list_clients,
+            commands::get_clients,
             commands::set_client,
             commands::show_client,
-            commands::get_running_scripts
+            commands::set_client_label,
+            commands::get_layouts,
+            commands::set_layout,
+            commands::save_current_layout,
+            commands::apply_layout,
+            commands::get_client_rules,
+            commands::set_client_rules,
+            commands::get_client_accounts,
+            commands::set_client_account,
+            commands::launch_client,
+            commands::get_game_clients,
+            commands::get_running_scripts,
+            commands::get_script_stats,
+            commands::get_script_progress,
+            commands::get_run_history,
+            commands::get_daily_runtime,
+            commands::get_launch_profiles,
+            commands::set_launch_profile,
+            commands::get_max_scripts,
+            commands::set_max_scripts,
+            commands::get_launch_queue,
+            commands::cancel_launch,
+            commands::get_stop_on_client_lost,
+            commands::set_stop_on_client_lost,
+            commands::export_settings,
+            commands::preview_settings_import,
+            commands::import_settings,
+            commands::get_control_settings,
+            commands::set_control_settings,
+            commands::get_metrics_settings,
+            commands::set_metrics_settings,
+            commands::get_webhooks,
+            commands::set_webhooks,
+            commands::send_test_webhook,
+            commands::get_notifications,
+            commands::set_notifications,
+            commands::get_triggers,
+            commands::set_triggers,
+            commands::test_triggers
         ])
         .run(tauri::generate_context!())
         .expect("Error while running wasp-launcher");
@@ -0,0 +1,370 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::{scripts::ExitReason, LauncherVariables};
+
+// Scrapers send a short GET, anything bigger is not one of them.
+const MAX_HEAD_LENGTH: u64 = 8 * 1024;
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Serves Prometheus metrics on localhost. Changes apply on the next start.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetricsSettings {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for MetricsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 9478,
+        }
+    }
+}
+
+/// Process wide counters. Downloads happen deep in `simba.rs` without an app
+/// handle, so these live in a static rather than in `LauncherVariables`.
+pub struct Metrics {
+    scripts_started: AtomicU64,
+    scripts_restarted: AtomicU64,
+    exits: Mutex<BTreeMap<(&'static str, String), u64>>,
+    download_bytes: AtomicU64,
+    download_failures: AtomicU64,
+    plugin_sync: Mutex<Option<(bool, u64)>>,
+}
+
+pub static METRICS: Metrics = Metrics {
+    scripts_started: AtomicU64::new(0),
+    scripts_restarted: AtomicU64::new(0),
+    exits: Mutex::new(BTreeMap::new()),
+    download_bytes: AtomicU64::new(0),
+    download_failures: AtomicU64::new(0),
+    plugin_sync: Mutex::new(None),
+};
+
+fn reason_label(reason: ExitReason) -> &'static str {
+    match reason {
+        ExitReason::Exited => "exited",
+        ExitReason::Killed => "killed",
+        ExitReason::MemoryLimit => "memory_limit",
+        ExitReason::ClientLost => "client_lost",
+        ExitReason::Inactive => "inactive",
+        ExitReason::Triggered => "triggered",
+    }
+}
+
+impl Metrics {
+    pub fn script_started(&self) {
+        self.scripts_started.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn script_restarted(&self) {
+        self.scripts_restarted.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn script_exited(&self, reason: ExitReason, code: Option<i32>) {
+        let code = code.map(|c| c.to_string()).unwrap_or_default();
+        let mut exits = self.exits.lock().unwrap();
+        *exits.entry((reason_label(reason), code)).or_default() += 1;
+    }
+
+    pub fn downloaded(&self, bytes: usize) {
+        self.download_bytes
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn download_failed(&self) {
+        self.download_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn plugins_synced(&self, success: bool, at: u64) {
+        *self.plugin_sync.lock().unwrap() = Some((success, at));
+    }
+}
+
+fn metric(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Renders every metric in the Prometheus text exposition format.
+pub fn render(app: &tauri::AppHandle) -> String {
+    let (running, records, stats) = {
+        let launcher = app.state::<Mutex<LauncherVariables>>();
+        let launcher = launcher.lock().unwrap();
+        // The registry also lists scripts of other launcher instances.
+        let running = launcher.scripts.lock().unwrap().len();
+        let records = launcher.registry.lock().unwrap().records();
+        let stats: Vec<_> = launcher.stats.lock().unwrap().values().cloned().collect();
+        (running, records, stats)
+    };
+
+    let mut out = String::new();
+
+    metric(
+        &mut out,
+        "wasp_scripts_running",
+        "gauge",
+        "Scripts currently running.",
+    );
+    let _ = writeln!(out, "wasp_scripts_running {}", running);
+
+    metric(
+        &mut out,
+        "wasp_scripts_started_total",
+        "counter",
+        "Scripts started by this launcher.",
+    );
+    let _ = writeln!(
+        out,
+        "wasp_scripts_started_total {}",
+        METRICS.scripts_started.load(Ordering::Relaxed)
+    );
+
+    metric(
+        &mut out,
+        "wasp_scripts_restarted_total",
+        "counter",
+        "Scripts restarted by the launcher.",
+    );
+    let _ = writeln!(
+        out,
+        "wasp_scripts_restarted_total {}",
+        METRICS.scripts_restarted.load(Ordering::Relaxed)
+    );
+
+    metric(
+        &mut out,
+        "wasp_script_exits_total",
+        "counter",
+        "Script exits by reason and exit code.",
+    );
+    for ((reason, code), count) in METRICS.exits.lock().unwrap().iter() {
+        let _ = writeln!(
+            out,
+            "wasp_script_exits_total{{reason=\"{}\",code=\"{}\"}} {}",
+            reason, code, count
+        );
+    }
+
+    metric(
+        &mut out,
+        "wasp_download_bytes_total",
+        "counter",
+        "Bytes downloaded for Simba, WaspLib and plugins.",
+    );
+    let _ = writeln!(
+        out,
+        "wasp_download_bytes_total {}",
+        METRICS.download_bytes.load(Ordering::Relaxed)
+    );
+
+    metric(
+        &mut out,
+        "wasp_download_failures_total",
+        "counter",
+        "Failed downloads.",
+    );
+    let _ = writeln!(
+        out,
+        "wasp_download_failures_total {}",
+        METRICS.download_failures.load(Ordering::Relaxed)
+    );
+
+    if let Some((success, at)) = *METRICS.plugin_sync.lock().unwrap() {
+        metric(
+            &mut out,
+            "wasp_plugins_sync_success",
+            "gauge",
+            "Whether the last plugin sync succeeded.",
+        );
+        let _ = writeln!(out, "wasp_plugins_sync_success {}", success as u8);
+        metric(
+            &mut out,
+            "wasp_plugins_sync_timestamp_seconds",
+            "gauge",
+            "Time of the last plugin sync.",
+        );
+        let _ = writeln!(out, "wasp_plugins_sync_timestamp_seconds {}", at);
+    }
+
+    let script_id = |id: u32| {
+        records
+            .iter()
+            .find(|record| record.id == id)
+            .map(|record| escape(&record.script_id))
+            .unwrap_or_default()
+    };
+
+    metric(
+        &mut out,
+        "wasp_script_cpu_percent",
+        "gauge",
+        "CPU usage of a script as a percentage of one core.",
+    );
+    for stat in &stats {
+        let _ = writeln!(
+            out,
+            "wasp_script_cpu_percent{{id=\"{}\",script=\"{}\"}} {}",
+            stat.id,
+            script_id(stat.id),
+            stat.cpu
+        );
+    }
+
+    metric(
+        &mut out,
+        "wasp_script_memory_bytes",
+        "gauge",
+        "Resident memory of a script.",
+    );
+    for stat in &stats {
+        let _ = writeln!(
+            out,
+            "wasp_script_memory_bytes{{id=\"{}\",script=\"{}\"}} {}",
+            stat.id,
+            script_id(stat.id),
+            stat.memory
+        );
+    }
+
+    metric(
+        &mut out,
+        "wasp_script_uptime_seconds",
+        "gauge",
+        "Seconds since a script was started.",
+    );
+    for stat in &stats {
+        let _ = writeln!(
+            out,
+            "wasp_script_uptime_seconds{{id=\"{}\",script=\"{}\"}} {}",
+            stat.id,
+            script_id(stat.id),
+            stat.uptime
+        );
+    }
+
+    out
+}
+
+/// Reads the request head up to the blank line ending the headers and returns the
+/// request path. `None` if the head is malformed or larger than `MAX_HEAD_LENGTH`.
+fn read_request_path<R: BufRead>(reader: R) -> Option<String> {
+    let mut reader = reader.take(MAX_HEAD_LENGTH);
+    let mut path = None;
+
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).ok()? == 0 || !line.ends_with('\n') {
+            return None;
+        }
+        if line.trim_end().is_empty() {
+            return path;
+        }
+        if path.is_none() {
+            path = Some(line.split_whitespace().nth(1)?.to_string());
+        }
+    }
+}
+
+fn handle_request(mut stream: TcpStream, app: &tauri::AppHandle) {
+    let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+    let Some(path) = read_request_path(BufReader::new(&stream)) else {
+        return;
+    };
+
+    let response = if path == "/metrics" {
+        let body = render(app);
+        format!(
+            "HTTP/1.1 200 OK\r\n\
+             Content-Type: text/plain; version=0.0.4\r\n\
+             Connection: close\r\n\
+             Content-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )
+    } else {
+        "HTTP/1.1 404 Not Found\r\nConnection: close\r\nContent-Length: 0\r\n\r\n".to_string()
+    };
+
+    let _ = stream.write_all(response.as_bytes());
+    let _ = stream.flush();
+}
+
+pub fn start_metrics_server(app: tauri::AppHandle, settings: MetricsSettings) {
+    if !settings.enabled {
+        return;
+    }
+
+    let listener = match TcpListener::bind(("127.0.0.1", settings.port)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Failed to start metrics server: {}", e);
+            return;
+        }
+    };
+
+    println!("Metrics available on localhost:{}/metrics", settings.port);
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let app = app.clone();
+                    thread::spawn(move || handle_request(stream, &app));
+                }
+                Err(e) => eprintln!("Error: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn reads_the_path_once_the_headers_end() {
+        let request = "GET /metrics HTTP/1.1\r\nHost: localhost\r\nAccept: */*\r\n\r\n";
+        assert_eq!(
+            read_request_path(Cursor::new(request)).as_deref(),
+            Some("/metrics")
+        );
+    }
+
+    #[test]
+    fn rejects_incomplete_and_oversized_requests() {
+        assert!(read_request_path(Cursor::new("GET /metrics HTTP/1.1\r\nHost: x")).is_none());
+        assert!(read_request_path(Cursor::new("\r\n")).is_none());
+
+        let long = format!(
+            "GET /metrics HTTP/1.1\r\nCookie: {}\r\n\r\n",
+            "a".repeat(MAX_HEAD_LENGTH as usize)
+        );
+        assert!(read_request_path(Cursor::new(long)).is_none());
+    }
+}
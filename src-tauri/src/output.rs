@@ -0,0 +1,78 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use regex::Regex;
+
+use crate::{progress::ScriptProgress, triggers::Trigger};
+
+pub type TriggerHandler = Box<dyn Fn(&Trigger, &str) + Send + Sync>;
+pub type ProgressHandler = Box<dyn Fn(ScriptProgress) + Send + Sync>;
+
+/// State shared by the stdout and stderr reader threads of a script.
+pub struct ScriptOutput {
+    last_activity: Mutex<Instant>,
+    heartbeat: Option<Regex>,
+    triggers: Vec<Trigger>,
+    on_trigger: Option<TriggerHandler>,
+    on_progress: Option<ProgressHandler>,
+}
+
+impl ScriptOutput {
+    /// Without a `heartbeat` any output line counts as activity.
+    pub fn new(heartbeat: Option<Regex>) -> Self {
+        Self {
+            last_activity: Mutex::new(Instant::now()),
+            heartbeat,
+            triggers: Vec::new(),
+            on_trigger: None,
+            on_progress: None,
+        }
+    }
+
+    /// Calls `handler` for every line matching one of `triggers`.
+    pub fn with_triggers(mut self, triggers: Vec<Trigger>, handler: TriggerHandler) -> Self {
+        self.triggers = triggers;
+        self.on_trigger = Some(handler);
+        self
+    }
+
+    /// Passes progress reports to `handler` instead of treating them as output.
+    pub fn with_progress(mut self, handler: ProgressHandler) -> Self {
+        self.on_progress = Some(handler);
+        self
+    }
+
+    /// Called by the stdout reader before `line`. Returns true if `line` was a progress
+    /// report, which is handled here and left out of the log.
+    pub fn progress(&self, line: &str) -> bool {
+        let Some(handler) = &self.on_progress else {
+            return false;
+        };
+        let Some(report) = ScriptProgress::parse(line) else {
+            return false;
+        };
+
+        *self.last_activity.lock().unwrap() = Instant::now();
+        handler(report);
+        true
+    }
+
+    /// Called by the reader threads for every line the script prints.
+    pub fn line(&self, line: &str) {
+        if self.heartbeat.as_ref().is_none_or(|r| r.is_match(line)) {
+            *self.last_activity.lock().unwrap() = Instant::now();
+        }
+
+        if let Some(handler) = &self.on_trigger {
+            for trigger in self.triggers.iter().filter(|t| t.is_match(line)) {
+                handler(trigger, line);
+            }
+        }
+    }
+
+    pub fn idle(&self) -> Duration {
+        self.last_activity.lock().unwrap().elapsed()
+    }
+}
@@ -0,0 +1,240 @@
+use std::{
+    fs, io,
+    os::unix::process::CommandExt,
+    path::{Path, PathBuf},
+    process::{Child, Command},
+    time::Duration,
+};
+
+use super::{ProcessLimiter, ProcessSample, ProcessSampler, ResourceLimits, MAX_CPUS};
+
+/// Read-only view over a `/proc` style directory.
+/// The root is configurable so the parsing can be pointed at a fake tree.
+#[derive(Debug, Clone)]
+pub struct Procfs {
+    root: PathBuf,
+}
+
+impl Default for Procfs {
+    fn default() -> Self {
+        Self::new("/proc")
+    }
+}
+
+impl Procfs {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn pid_dir(&self, pid: u32) -> PathBuf {
+        self.root.join(pid.to_string())
+    }
+
+    pub fn pids(&self) -> Vec<u32> {
+        let Ok(entries) = fs::read_dir(&self.root) else {
+            return Vec::new();
+        };
+
+        entries
+            .flatten()
+            .filter_map(|entry| entry.file_name().to_str()?.parse().ok())
+            .collect()
+    }
+
+    pub fn comm(&self, pid: u32) -> Option<String> {
+        fs::read_to_string(self.pid_dir(pid).join("comm"))
+            .ok()
+            .map(|comm| comm.trim().to_string())
+    }
+
+    pub fn cmdline(&self, pid: u32) -> Vec<String> {
+        read_nul_separated(&self.pid_dir(pid).join("cmdline"))
+    }
+
+    pub fn exe(&self, pid: u32) -> Option<PathBuf> {
+        fs::read_link(self.pid_dir(pid).join("exe")).ok()
+    }
+
+    pub fn environ(&self, pid: u32) -> Vec<(String, String)> {
+        read_nul_separated(&self.pid_dir(pid).join("environ"))
+            .into_iter()
+            .filter_map(|var| {
+                let (key, value) = var.split_once('=')?;
+                Some((key.to_string(), value.to_string()))
+            })
+            .collect()
+    }
+
+    /// Fields of `/proc/<pid>/stat` that come after the `(comm)` entry.
+    /// Index 0 is the process state (field 3 in `man proc`).
+    pub fn stat_fields(&self, pid: u32) -> Option<Vec<String>> {
+        let stat = fs::read_to_string(self.pid_dir(pid).join("stat")).ok()?;
+        let rest = &stat[stat.rfind(')')? + 1..];
+        Some(rest.split_whitespace().map(str::to_string).collect())
+    }
+
+    /// Boot time in seconds since the unix epoch, from the `btime` line of `/proc/stat`.
+    pub fn boot_time(&self) -> Option<u64> {
+        let stat = fs::read_to_string(self.root.join("stat")).ok()?;
+        stat.lines()
+            .find_map(|l| l.strip_prefix("btime "))
+            .and_then(|v| v.trim().parse().ok())
+    }
+
+    /// Start time of `pid` in seconds since the unix epoch.
+    pub fn start_time(&self, pid: u32) -> Option<u64> {
+        let fields = self.stat_fields(pid)?;
+        let ticks: u64 = fields.get(19)?.parse().ok()?;
+        Some(self.boot_time()? + ticks / clock_ticks())
+    }
+
+    pub fn is_running(&self, pid: u32) -> bool {
+        match self.stat_fields(pid) {
+            Some(fields) => fields.first().map(String::as_str) != Some("Z"),
+            None => false,
+        }
+    }
+}
+
+fn read_nul_separated(path: &Path) -> Vec<String> {
+    match fs::read(path) {
+        Ok(data) => data
+            .split(|b| *b == 0)
+            .filter(|part| !part.is_empty())
+            .map(|part| String::from_utf8_lossy(part).into_owned())
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+impl ProcessSampler for Procfs {
+    fn sample(&self, pid: u32) -> Option<ProcessSample> {
+        let fields = self.stat_fields(pid)?;
+        let utime: u64 = fields.get(11)?.parse().ok()?;
+        let stime: u64 = fields.get(12)?.parse().ok()?;
+        let ticks = utime + stime;
+
+        let status = fs::read_to_string(self.pid_dir(pid).join("status")).ok()?;
+        let rss_kb: u64 = status
+            .lines()
+            .find_map(|l| l.strip_prefix("VmRSS:"))
+            .and_then(|v| v.trim().trim_end_matches("kB").trim().parse().ok())
+            .unwrap_or(0);
+
+        Some(ProcessSample {
+            cpu_time: Duration::from_millis(ticks * 1000 / clock_ticks()),
+            memory: rss_kb * 1024,
+        })
+    }
+}
+
+pub fn platform_sampler() -> Box<dyn ProcessSampler> {
+    Box::new(Procfs::default())
+}
+
+pub struct RlimitLimiter;
+
+/// Applies priority and affinity before exec. Memory is left to the script watcher,
+/// since RLIMIT_AS caps address space rather than resident memory.
+impl ProcessLimiter for RlimitLimiter {
+    fn configure(&self, cmd: &mut Command, limits: &ResourceLimits) {
+        if !limits.needs_limiter() {
+            return;
+        }
+
+        let limits = limits.clone();
+        // Runs in the forked child right before exec, so only async-signal-safe calls here.
+        unsafe {
+            cmd.pre_exec(move || {
+                if let Some(priority) = limits.priority {
+                    if libc::setpriority(libc::PRIO_PROCESS, 0, priority) != 0 {
+                        return Err(io::Error::last_os_error());
+                    }
+                }
+
+                if let Some(cpus) = &limits.affinity {
+                    let mut set: libc::cpu_set_t = std::mem::zeroed();
+                    // CPU_SET panics past CPU_SETSIZE, which must not happen in the child.
+                    for cpu in cpus.iter().filter(|cpu| **cpu < MAX_CPUS) {
+                        libc::CPU_SET(*cpu, &mut set);
+                    }
+                    let size = std::mem::size_of::<libc::cpu_set_t>();
+                    if libc::sched_setaffinity(0, size, &set) != 0 {
+                        return Err(io::Error::last_os_error());
+                    }
+                }
+
+                Ok(())
+            });
+        }
+    }
+
+    fn attach(&self, _child: &Child, _limits: &ResourceLimits) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+pub fn platform_limiter() -> Box<dyn ProcessLimiter> {
+    Box::new(RlimitLimiter)
+}
+
+pub fn clock_ticks() -> u64 {
+    let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    if ticks > 0 {
+        ticks as u64
+    } else {
+        100
+    }
+}
+
+pub fn process_start_time(pid: u32) -> Option<u64> {
+    Procfs::default().start_time(pid)
+}
+
+pub fn is_running(pid: u32) -> bool {
+    Procfs::default().is_running(pid)
+}
+
+pub fn kill_process(pid: u32) -> io::Result<()> {
+    if unsafe { libc::kill(pid as libc::pid_t, libc::SIGKILL) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_a_fake_process() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join("42");
+        fs::create_dir(&dir).unwrap();
+        fs::write(dir.join("comm"), "java\n").unwrap();
+        fs::write(dir.join("cmdline"), b"java\0-jar\0Run Lite.jar\0").unwrap();
+        // The command name may itself contain spaces and parentheses.
+        fs::write(dir.join("stat"), "42 (my (odd) java) S 1 42 42 0").unwrap();
+        fs::write(root.path().join("stat"), "cpu 1 2 3\nbtime 1700000000\n").unwrap();
+        fs::create_dir(root.path().join("self")).unwrap();
+
+        let procfs = Procfs::new(root.path());
+        assert_eq!(procfs.pids(), vec![42]);
+        assert_eq!(procfs.comm(42).as_deref(), Some("java"));
+        assert_eq!(procfs.cmdline(42), vec!["java", "-jar", "Run Lite.jar"]);
+        assert_eq!(procfs.stat_fields(42).unwrap()[..2], ["S", "1"]);
+        assert_eq!(procfs.boot_time(), Some(1_700_000_000));
+        assert!(procfs.is_running(42));
+        assert!(!procfs.is_running(43));
+    }
+
+    #[test]
+    fn zombies_are_not_running() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join("7");
+        fs::create_dir(&dir).unwrap();
+        fs::write(dir.join("stat"), "7 (java) Z 1 7 7 0").unwrap();
+
+        assert!(!Procfs::new(root.path()).is_running(7));
+    }
+}
@@ -0,0 +1,30 @@
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use super::{ProcessSample, ProcessSampler};
+
+/// Sampler reporting preset usage for fake processes, for tests.
+#[derive(Default)]
+pub struct MockSampler {
+    samples: Mutex<HashMap<u32, ProcessSample>>,
+}
+
+impl MockSampler {
+    /// Sets what process `pid` has used so far.
+    pub fn set(&self, pid: u32, cpu_time: Duration, memory: u64) {
+        self.samples
+            .lock()
+            .unwrap()
+            .insert(pid, ProcessSample { cpu_time, memory });
+    }
+
+    /// Ends process `pid`, it can't be sampled anymore.
+    pub fn exit(&self, pid: u32) {
+        self.samples.lock().unwrap().remove(&pid);
+    }
+}
+
+impl ProcessSampler for MockSampler {
+    fn sample(&self, pid: u32) -> Option<ProcessSample> {
+        self.samples.lock().unwrap().get(&pid).copied()
+    }
+}
@@ -0,0 +1,147 @@
+use std::{
+    io,
+    ops::RangeInclusive,
+    process::{Child, Command},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+pub use self::mock::MockSampler;
+
+#[cfg(target_os = "windows")]
+mod windows;
+#[cfg(target_os = "windows")]
+pub use self::windows::{
+    is_running, kill_process, platform_limiter, platform_sampler, process_start_time,
+};
+
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "linux")]
+pub use self::linux::{
+    is_running, kill_process, platform_limiter, platform_sampler, process_start_time, Procfs,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub struct ProcessSample {
+    /// Total CPU time (user + kernel) consumed since the process started.
+    pub cpu_time: Duration,
+    /// Resident memory in bytes.
+    pub memory: u64,
+}
+
+pub trait ProcessSampler: Send + Sync {
+    fn sample(&self, pid: u32) -> Option<ProcessSample>;
+}
+
+/// Number of CPUs an affinity mask can address: the bits of a job object's mask on
+/// Windows, `CPU_SETSIZE` on Linux.
+#[cfg(target_os = "windows")]
+pub const MAX_CPUS: usize = usize::BITS as usize;
+#[cfg(not(target_os = "windows"))]
+pub const MAX_CPUS: usize = 1024;
+
+/// Niceness values `setpriority` accepts, mapped onto priority classes on Windows.
+const PRIORITY_RANGE: RangeInclusive<i32> = -20..=19;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResourceLimits {
+    /// Resident memory ceiling in bytes. The script watcher kills the script once its
+    /// resident memory goes over it, on every platform.
+    pub memory: Option<u64>,
+    /// Unix style niceness, from -20 (highest priority) to 19 (lowest).
+    pub priority: Option<i32>,
+    /// Indices of the CPUs the process is allowed to run on.
+    pub affinity: Option<Vec<usize>>,
+}
+
+impl ResourceLimits {
+    /// Whether the platform limiter has anything to apply. Memory is enforced by the
+    /// script watcher instead.
+    pub fn needs_limiter(&self) -> bool {
+        self.priority.is_some() || self.affinity.is_some()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(priority) = self.priority.filter(|p| !PRIORITY_RANGE.contains(p)) {
+            return Err(format!(
+                "Priority {} is out of range, it must be between {} and {}",
+                priority,
+                PRIORITY_RANGE.start(),
+                PRIORITY_RANGE.end()
+            ));
+        }
+        if self.affinity.as_ref().is_some_and(Vec::is_empty) {
+            return Err("CPU affinity must list at least one CPU".to_string());
+        }
+        if let Some(cpu) = self.affinity.iter().flatten().find(|cpu| **cpu >= MAX_CPUS) {
+            return Err(format!(
+                "CPU index {} is out of range, the highest supported is {}",
+                cpu,
+                MAX_CPUS - 1
+            ));
+        }
+        Ok(())
+    }
+}
+
+pub trait ProcessLimiter: Send + Sync {
+    /// Called before the process is spawned.
+    fn configure(&self, cmd: &mut Command, limits: &ResourceLimits);
+    /// Called right after the process was spawned.
+    fn attach(&self, child: &Child, limits: &ResourceLimits) -> io::Result<()>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_rejects_out_of_range_cpus() {
+        let limits = ResourceLimits {
+            affinity: Some(vec![0, MAX_CPUS - 1]),
+            ..Default::default()
+        };
+        assert!(limits.validate().is_ok());
+
+        let limits = ResourceLimits {
+            affinity: Some(vec![0, MAX_CPUS]),
+            ..Default::default()
+        };
+        assert!(limits.validate().is_err());
+    }
+
+    #[test]
+    fn validate_rejects_empty_affinity() {
+        let limits = ResourceLimits {
+            affinity: Some(vec![]),
+            ..Default::default()
+        };
+        assert!(limits.validate().is_err());
+    }
+
+    #[test]
+    fn validate_rejects_out_of_range_priorities() {
+        for (priority, valid) in [(-20, true), (19, true), (-21, false), (20, false)] {
+            let limits = ResourceLimits {
+                priority: Some(priority),
+                ..Default::default()
+            };
+            assert_eq!(limits.validate().is_ok(), valid, "priority {}", priority);
+        }
+    }
+
+    #[test]
+    fn memory_alone_needs_no_limiter() {
+        let limits = ResourceLimits {
+            memory: Some(1 << 30),
+            ..Default::default()
+        };
+        assert!(!limits.needs_limiter());
+    }
+}
@@ -0,0 +1,184 @@
+use std::{
+    io,
+    os::windows::io::AsRawHandle,
+    process::{Child, Command},
+    time::Duration,
+};
+
+use windows::core::PCWSTR;
+use windows::Win32::Foundation::{CloseHandle, FILETIME, HANDLE, STILL_ACTIVE};
+use windows::Win32::System::JobObjects::{
+    AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation,
+    SetInformationJobObject, JOBOBJECT_EXTENDED_LIMIT_INFORMATION, JOB_OBJECT_LIMIT,
+    JOB_OBJECT_LIMIT_AFFINITY, JOB_OBJECT_LIMIT_PRIORITY_CLASS,
+};
+use windows::Win32::System::ProcessStatus::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS};
+use windows::Win32::System::Threading::{
+    GetExitCodeProcess, GetProcessTimes, OpenProcess, TerminateProcess,
+    ABOVE_NORMAL_PRIORITY_CLASS, BELOW_NORMAL_PRIORITY_CLASS, HIGH_PRIORITY_CLASS,
+    IDLE_PRIORITY_CLASS, NORMAL_PRIORITY_CLASS, PROCESS_ACCESS_RIGHTS,
+    PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_TERMINATE,
+};
+
+use super::{ProcessLimiter, ProcessSample, ProcessSampler, ResourceLimits};
+
+// Seconds between 1601-01-01 (FILETIME epoch) and 1970-01-01.
+const EPOCH_DIFFERENCE: u64 = 11_644_473_600;
+
+fn open_process(access: PROCESS_ACCESS_RIGHTS, pid: u32) -> Option<HANDLE> {
+    unsafe { OpenProcess(access, false, pid).ok() }
+}
+
+struct ProcessTimes {
+    creation: u64,
+    kernel: u64,
+    user: u64,
+}
+
+fn filetime_to_u64(time: FILETIME) -> u64 {
+    ((time.dwHighDateTime as u64) << 32) | time.dwLowDateTime as u64
+}
+
+fn process_times(pid: u32) -> Option<ProcessTimes> {
+    let handle = open_process(PROCESS_QUERY_LIMITED_INFORMATION, pid)?;
+
+    let mut creation = FILETIME::default();
+    let mut exit = FILETIME::default();
+    let mut kernel = FILETIME::default();
+    let mut user = FILETIME::default();
+
+    let result =
+        unsafe { GetProcessTimes(handle, &mut creation, &mut exit, &mut kernel, &mut user) };
+    unsafe {
+        let _ = CloseHandle(handle);
+    }
+    result.ok()?;
+
+    Some(ProcessTimes {
+        creation: filetime_to_u64(creation),
+        kernel: filetime_to_u64(kernel),
+        user: filetime_to_u64(user),
+    })
+}
+
+fn process_memory(pid: u32) -> Option<u64> {
+    let handle = open_process(PROCESS_QUERY_LIMITED_INFORMATION, pid)?;
+
+    let mut counters = PROCESS_MEMORY_COUNTERS::default();
+    let size = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+    let result = unsafe { GetProcessMemoryInfo(handle, &mut counters, size) };
+    unsafe {
+        let _ = CloseHandle(handle);
+    }
+    result.ok()?;
+
+    Some(counters.WorkingSetSize as u64)
+}
+
+pub fn process_start_time(pid: u32) -> Option<u64> {
+    let times = process_times(pid)?;
+    Some((times.creation / 10_000_000).saturating_sub(EPOCH_DIFFERENCE))
+}
+
+pub struct WindowsSampler;
+
+impl ProcessSampler for WindowsSampler {
+    fn sample(&self, pid: u32) -> Option<ProcessSample> {
+        let times = process_times(pid)?;
+        Some(ProcessSample {
+            // FILETIME durations are in 100 nanosecond intervals.
+            cpu_time: Duration::from_nanos((times.kernel + times.user) * 100),
+            memory: process_memory(pid)?,
+        })
+    }
+}
+
+pub fn platform_sampler() -> Box<dyn ProcessSampler> {
+    Box::new(WindowsSampler)
+}
+
+pub fn is_running(pid: u32) -> bool {
+    let Some(handle) = open_process(PROCESS_QUERY_LIMITED_INFORMATION, pid) else {
+        return false;
+    };
+
+    let mut code = 0u32;
+    let result = unsafe { GetExitCodeProcess(handle, &mut code) };
+    unsafe {
+        let _ = CloseHandle(handle);
+    }
+
+    result.is_ok() && code == STILL_ACTIVE.0 as u32
+}
+
+pub fn kill_process(pid: u32) -> io::Result<()> {
+    let handle = open_process(PROCESS_TERMINATE, pid).ok_or_else(io::Error::last_os_error)?;
+    let result = unsafe { TerminateProcess(handle, 1) };
+    unsafe {
+        let _ = CloseHandle(handle);
+    }
+    result.map_err(|e| io::Error::other(e.to_string()))
+}
+
+pub struct JobObjectLimiter;
+
+fn priority_class(niceness: i32) -> u32 {
+    match niceness {
+        i32::MIN..=-15 => HIGH_PRIORITY_CLASS.0,
+        -14..=-1 => ABOVE_NORMAL_PRIORITY_CLASS.0,
+        0 => NORMAL_PRIORITY_CLASS.0,
+        1..=14 => BELOW_NORMAL_PRIORITY_CLASS.0,
+        _ => IDLE_PRIORITY_CLASS.0,
+    }
+}
+
+impl ProcessLimiter for JobObjectLimiter {
+    fn configure(&self, _cmd: &mut Command, _limits: &ResourceLimits) {}
+
+    fn attach(&self, child: &Child, limits: &ResourceLimits) -> io::Result<()> {
+        // Memory is left to the script watcher, a job's process memory limit caps
+        // committed memory rather than the working set the watcher samples.
+        if !limits.needs_limiter() {
+            return Ok(());
+        }
+
+        let mut info = JOBOBJECT_EXTENDED_LIMIT_INFORMATION::default();
+        let mut flags = JOB_OBJECT_LIMIT(0);
+
+        if let Some(priority) = limits.priority {
+            info.BasicLimitInformation.PriorityClass = priority_class(priority);
+            flags |= JOB_OBJECT_LIMIT_PRIORITY_CLASS;
+        }
+
+        if let Some(cpus) = &limits.affinity {
+            info.BasicLimitInformation.Affinity = cpus
+                .iter()
+                .filter_map(|cpu| 1usize.checked_shl(*cpu as u32))
+                .fold(0, |mask, bit| mask | bit);
+            flags |= JOB_OBJECT_LIMIT_AFFINITY;
+        }
+
+        info.BasicLimitInformation.LimitFlags = flags;
+
+        unsafe {
+            let job = CreateJobObjectW(None, PCWSTR::null())
+                .map_err(|e| io::Error::other(e.to_string()))?;
+
+            let result = SetInformationJobObject(
+                job,
+                JobObjectExtendedLimitInformation,
+                &info as *const _ as *const core::ffi::c_void,
+                std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+            )
+            .and_then(|_| AssignProcessToJobObject(job, HANDLE(child.as_raw_handle())));
+
+            // The job object stays alive for as long as a process is assigned to it.
+            let _ = CloseHandle(job);
+            result.map_err(|e| io::Error::other(e.to_string()))
+        }
+    }
+}
+
+pub fn platform_limiter() -> Box<dyn ProcessLimiter> {
+    Box::new(JobObjectLimiter)
+}
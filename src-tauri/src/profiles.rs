@@ -0,0 +1,37 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{process::ResourceLimits, watchdog::WatchdogConfig};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LaunchProfile {
+    pub limits: ResourceLimits,
+    pub watchdog: WatchdogConfig,
+}
+
+impl LaunchProfile {
+    pub fn validate(&self) -> Result<(), String> {
+        self.limits.validate()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClientAccount {
+    /// Either "runelite" or "osclient".
+    pub client: String,
+    pub args: Vec<String>,
+    pub env: HashMap<String, String>,
+}
+
+impl Default for ClientAccount {
+    fn default() -> Self {
+        Self {
+            client: "runelite".to_string(),
+            args: Vec::new(),
+            env: HashMap::new(),
+        }
+    }
+}
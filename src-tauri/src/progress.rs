@@ -0,0 +1,119 @@
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use tauri::{Emitter, Manager};
+
+use crate::{scripts::unix_now, LauncherVariables};
+
+/// Scripts report progress by printing this marker followed by a JSON object on
+/// stdout, e.g. `::wasp-progress:: {"xp": 12000, "status": "Banking"}`. Such lines
+/// are kept out of the log and only the fields present in a report are updated.
+pub const PROGRESS_MARKER: &str = "::wasp-progress::";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScriptProgress {
+    pub xp: Option<u64>,
+    /// Milliseconds the script has been running, as the script counts it.
+    pub runtime: Option<u64>,
+    pub status: Option<String>,
+    /// Any other fields the script reports, kept as they were sent.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+    /// When the last report arrived, set by the launcher.
+    #[serde(default)]
+    pub updated: u64,
+}
+
+impl ScriptProgress {
+    /// Returns the report carried by `line`, or `None` for ordinary output. Lines with
+    /// the marker but invalid JSON count as ordinary output so the author sees them.
+    pub fn parse(line: &str) -> Option<Self> {
+        let data = line.trim_start().strip_prefix(PROGRESS_MARKER)?;
+        serde_json::from_str(data.trim()).ok()
+    }
+
+    pub fn merge(&mut self, report: ScriptProgress) {
+        if report.xp.is_some() {
+            self.xp = report.xp;
+        }
+        if report.runtime.is_some() {
+            self.runtime = report.runtime;
+        }
+        if report.status.is_some() {
+            self.status = report.status;
+        }
+        self.extra.extend(report.extra);
+        self.updated = unix_now();
+    }
+}
+
+/// Stores a report from script `id` and forwards the merged progress to the frontend.
+pub fn report_progress(app: &tauri::AppHandle, id: u32, report: ScriptProgress) {
+    let progress = {
+        let launcher = app.state::<Mutex<LauncherVariables>>();
+        let launcher = launcher.lock().unwrap();
+        let mut progress = launcher.progress.lock().unwrap();
+        let entry = progress.entry(id).or_default();
+        entry.merge(report);
+        entry.clone()
+    };
+
+    let _ = app.emit("script-progress", json!({ "id": id, "progress": progress }));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::output::ScriptOutput;
+
+    #[test]
+    fn parses_marked_lines() {
+        let report = ScriptProgress::parse(
+            r#"  ::wasp-progress:: {"xp": 12000, "status": "Banking", "ores": 3}"#,
+        )
+        .unwrap();
+        assert_eq!(report.xp, Some(12000));
+        assert_eq!(report.runtime, None);
+        assert_eq!(report.status.as_deref(), Some("Banking"));
+        assert_eq!(report.extra["ores"], 3);
+    }
+
+    #[test]
+    fn leaves_other_lines_as_output() {
+        assert!(ScriptProgress::parse(r#"{"xp": 12000}"#).is_none());
+        assert!(ScriptProgress::parse("Walking to bank ::wasp-progress:: {}").is_none());
+        assert!(ScriptProgress::parse("::wasp-progress:: {xp: 12000").is_none());
+
+        // The reader thread only swallows lines that parsed.
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let received = reports.clone();
+        let output = ScriptOutput::new(None).with_progress(Box::new(move |report| {
+            received.lock().unwrap().push(report)
+        }));
+        assert!(!output.progress("::wasp-progress:: {xp: 12000"));
+        assert!(output.progress(r#"::wasp-progress:: {"xp": 12000}"#));
+        assert_eq!(reports.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn merge_keeps_fields_missing_from_the_report() {
+        let mut progress = ScriptProgress::parse(
+            r#"::wasp-progress:: {"xp": 100, "status": "Mining", "ores": 1}"#,
+        )
+        .unwrap();
+        let report =
+            ScriptProgress::parse(r#"::wasp-progress:: {"runtime": 5000, "ores": 2, "gems": 1}"#)
+                .unwrap();
+        progress.merge(report);
+
+        assert_eq!(progress.xp, Some(100));
+        assert_eq!(progress.runtime, Some(5000));
+        assert_eq!(progress.status.as_deref(), Some("Mining"));
+        assert_eq!(progress.extra["ores"], 2);
+        assert_eq!(progress.extra["gems"], 1);
+        assert!(progress.updated > 0);
+    }
+}
@@ -0,0 +1,253 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    pin::pin,
+    sync::Mutex,
+};
+
+use serde_json::json;
+use tauri::Emitter;
+use tokio::sync::Notify;
+
+#[derive(Default)]
+struct QueueState {
+    max: usize,
+    active: HashSet<u32>,
+    waiting: VecDeque<u32>,
+}
+
+/// Called with a launch and its position in the queue, 1 being next, 0 once it got a
+/// slot and `None` once it was cancelled.
+pub type PositionListener = Box<dyn Fn(u32, Option<usize>) + Send + Sync>;
+
+/// FIFO queue of script launches. A launch holds a slot from the moment it starts
+/// provisioning until the script exits, and `max` of 0 means unlimited.
+#[derive(Default)]
+pub struct LaunchQueue {
+    state: Mutex<QueueState>,
+    notify: Notify,
+    on_position: Option<PositionListener>,
+}
+
+impl LaunchQueue {
+    pub fn new(max: usize) -> Self {
+        Self {
+            state: Mutex::new(QueueState {
+                max,
+                ..Default::default()
+            }),
+            notify: Notify::new(),
+            on_position: None,
+        }
+    }
+
+    /// Tells `listener` whenever the position of a launch changes.
+    pub fn with_listener(mut self, listener: PositionListener) -> Self {
+        self.on_position = Some(listener);
+        self
+    }
+
+    pub fn max(&self) -> usize {
+        self.state.lock().unwrap().max
+    }
+
+    pub fn set_max(&self, max: usize) {
+        self.state.lock().unwrap().max = max;
+        self.notify.notify_waiters();
+    }
+
+    pub fn waiting(&self) -> Vec<u32> {
+        self.state.lock().unwrap().waiting.iter().copied().collect()
+    }
+
+    /// Waits until `id` reaches the front of the queue and a slot is free. Returns false
+    /// if the launch was cancelled while it waited.
+    pub async fn acquire(&self, id: u32) -> bool {
+        self.state.lock().unwrap().waiting.push_back(id);
+        self.report_positions();
+
+        loop {
+            let mut notified = pin!(self.notify.notified());
+            notified.as_mut().enable();
+
+            match self.try_acquire(id) {
+                Some(true) => break,
+                Some(false) => return false,
+                None => notified.await,
+            }
+        }
+
+        self.report(id, Some(0));
+        self.report_positions();
+        true
+    }
+
+    /// Drops `id` from the queue, returns false if it wasn't waiting.
+    pub fn cancel(&self, id: u32) -> bool {
+        let removed = {
+            let mut state = self.state.lock().unwrap();
+            let before = state.waiting.len();
+            state.waiting.retain(|&waiting| waiting != id);
+            state.waiting.len() != before
+        };
+
+        if removed {
+            self.notify.notify_waiters();
+            self.report(id, None);
+            self.report_positions();
+        }
+        removed
+    }
+
+    /// Takes a slot regardless of the limit, used for scripts that are already running.
+    pub fn claim(&self, id: u32) {
+        self.state.lock().unwrap().active.insert(id);
+    }
+
+    pub fn release(&self, id: u32) {
+        let removed = {
+            let mut state = self.state.lock().unwrap();
+            state.active.remove(&id)
+        };
+
+        if removed {
+            self.notify.notify_waiters();
+            self.report_positions();
+        }
+    }
+
+    /// `Some(true)` once `id` holds a slot, `Some(false)` if it left the queue.
+    fn try_acquire(&self, id: u32) -> Option<bool> {
+        let mut state = self.state.lock().unwrap();
+        if !state.waiting.contains(&id) {
+            return Some(false);
+        }
+        let has_slot = state.max == 0 || state.active.len() < state.max;
+
+        if has_slot && state.waiting.front() == Some(&id) {
+            state.waiting.pop_front();
+            state.active.insert(id);
+            // Let the next entry in line re-check now that it moved up.
+            self.notify.notify_waiters();
+            return Some(true);
+        }
+
+        None
+    }
+
+    fn report(&self, id: u32, position: Option<usize>) {
+        if let Some(listener) = &self.on_position {
+            listener(id, position);
+        }
+    }
+
+    fn report_positions(&self) {
+        for (position, id) in self.waiting().into_iter().enumerate() {
+            self.report(id, Some(position + 1));
+        }
+    }
+}
+
+/// Forwards queue positions to the frontend as `queue-position` events.
+pub fn position_events(app: tauri::AppHandle) -> PositionListener {
+    Box::new(move |id, position| {
+        let _ = app.emit("queue-position", json!({ "id": id, "position": position }));
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use tauri::async_runtime::{self, JoinHandle};
+
+    use super::*;
+
+    // Starts `acquire(id)` in the background and waits until `id` is in line.
+    fn enqueue(queue: &Arc<LaunchQueue>, id: u32) -> JoinHandle<bool> {
+        let waiting = queue.clone();
+        let handle = async_runtime::spawn(async move { waiting.acquire(id).await });
+        while !queue.waiting().contains(&id) {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        handle
+    }
+
+    fn acquired(handle: JoinHandle<bool>) -> bool {
+        async_runtime::block_on(async {
+            tokio::time::timeout(Duration::from_secs(5), handle)
+                .await
+                .expect("launch never got a slot")
+                .unwrap()
+        })
+    }
+
+    #[test]
+    fn launches_in_arrival_order() {
+        let positions = Arc::new(Mutex::new(Vec::new()));
+        let reported = positions.clone();
+        let queue = Arc::new(
+            LaunchQueue::new(1).with_listener(Box::new(move |id, position| {
+                reported.lock().unwrap().push((id, position))
+            })),
+        );
+        queue.claim(1);
+
+        let second = enqueue(&queue, 2);
+        let third = enqueue(&queue, 3);
+        assert_eq!(queue.waiting(), vec![2, 3]);
+
+        queue.release(1);
+        assert!(acquired(second));
+        assert_eq!(queue.waiting(), vec![3]);
+
+        queue.release(2);
+        assert!(acquired(third));
+        assert!(positions.lock().unwrap().contains(&(3, Some(2))));
+        assert!(positions.lock().unwrap().contains(&(3, Some(0))));
+    }
+
+    #[test]
+    fn cancelled_launches_leave_the_queue() {
+        let queue = Arc::new(LaunchQueue::new(1));
+        queue.claim(1);
+
+        let second = enqueue(&queue, 2);
+        let third = enqueue(&queue, 3);
+        assert!(queue.cancel(2));
+        assert!(!queue.cancel(2));
+        assert!(!acquired(second));
+        assert_eq!(queue.waiting(), vec![3]);
+
+        queue.release(1);
+        assert!(acquired(third));
+    }
+
+    #[test]
+    fn try_acquire_waits_for_a_free_slot() {
+        let queue = LaunchQueue::new(2);
+        queue.claim(1);
+        queue.claim(2);
+        queue.state.lock().unwrap().waiting.push_back(3);
+
+        assert_eq!(queue.try_acquire(3), None);
+        queue.release(1);
+        assert_eq!(queue.try_acquire(3), Some(true));
+        assert_eq!(queue.try_acquire(4), Some(false));
+    }
+
+    #[test]
+    fn lowering_max_holds_launches_until_enough_scripts_exit() {
+        let queue = Arc::new(LaunchQueue::new(2));
+        queue.claim(1);
+        queue.claim(2);
+        queue.set_max(1);
+
+        let third = enqueue(&queue, 3);
+        queue.release(1);
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(queue.waiting(), vec![3]);
+
+        queue.release(2);
+        assert!(acquired(third));
+    }
+}
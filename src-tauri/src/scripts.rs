@@ -0,0 +1,1001 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::{self, create_dir_all, File},
+    io,
+    path::{Path, PathBuf},
+    process::Child,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{ipc::Channel, Emitter, Manager};
+
+use crate::{
+    client::{ClientProvider, WindowMatch, DEFAULT_CLIENT},
+    history::RunRecord,
+    metrics::METRICS,
+    output::ScriptOutput,
+    process::{is_running, kill_process, platform_sampler, process_start_time, ProcessSampler},
+    profiles::LaunchProfile,
+    progress::report_progress,
+    settings::SettingsStore,
+    simba::run_simba_script,
+    tray::{notify_desktop, refresh_tray},
+    triggers::{self, fire},
+    watchdog::start_watchdog,
+    webhooks::{notify, EventKind, ScriptEvent},
+    LauncherVariables,
+};
+
+// Allowed drift between the recorded and the live start time of a PID before we
+// consider it reused by an unrelated process.
+const START_TIME_TOLERANCE: u64 = 2;
+
+// Automatic restarts of one launch before the script is left stopped. The first waits
+// `RESTART_DELAY`, every further one twice as long as the one before.
+const MAX_RESTARTS: u32 = 5;
+const RESTART_DELAY: Duration = Duration::from_secs(2);
+// A run that lasted this many seconds was healthy, its restart count starts over.
+const STABLE_RUN: u64 = 10 * 60;
+
+pub type SharedProcess = Arc<Mutex<Option<ScriptProcess>>>;
+
+pub enum ScriptProcess {
+    Spawned(Child),
+    Adopted(u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitReason {
+    Exited,
+    Killed,
+    MemoryLimit,
+    ClientLost,
+    Inactive,
+    Triggered,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ScriptExit {
+    pub code: Option<i32>,
+    pub reason: ExitReason,
+}
+
+impl fmt::Display for ScriptExit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.reason, self.code) {
+            (ExitReason::MemoryLimit, _) => write!(f, "memory limit exceeded"),
+            (ExitReason::Killed, _) => write!(f, "killed"),
+            (ExitReason::ClientLost, _) => write!(f, "client lost"),
+            (ExitReason::Inactive, _) => write!(f, "no output"),
+            (ExitReason::Triggered, _) => write!(f, "stopped by trigger"),
+            (ExitReason::Exited, Some(code)) => write!(f, "exit code: {}", code),
+            (ExitReason::Exited, None) => write!(f, "unknown exit code"),
+        }
+    }
+}
+
+impl ScriptProcess {
+    pub fn pid(&self) -> u32 {
+        match self {
+            ScriptProcess::Spawned(child) => child.id(),
+            ScriptProcess::Adopted(pid) => *pid,
+        }
+    }
+
+    pub fn try_wait(&mut self) -> io::Result<Option<ScriptExit>> {
+        match self {
+            ScriptProcess::Spawned(child) => Ok(child.try_wait()?.map(|status| ScriptExit {
+                code: status.code(),
+                reason: ExitReason::Exited,
+            })),
+            ScriptProcess::Adopted(pid) => {
+                if is_running(*pid) {
+                    Ok(None)
+                } else {
+                    Ok(Some(ScriptExit {
+                        code: None,
+                        reason: ExitReason::Exited,
+                    }))
+                }
+            }
+        }
+    }
+
+    pub fn kill(&mut self) -> io::Result<()> {
+        match self {
+            ScriptProcess::Spawned(child) => child.kill(),
+            ScriptProcess::Adopted(pid) => kill_process(*pid),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptRecord {
+    pub id: u32,
+    pub pid: u32,
+    pub started: u64,
+    pub script_id: String,
+    pub log_path: PathBuf,
+    #[serde(default)]
+    pub memory_limit: Option<u64>,
+    #[serde(default)]
+    pub client: Option<WindowMatch>,
+    /// Set by `TriggerAction::Tag`.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Simba and WaspLib versions the script was provisioned with.
+    #[serde(default)]
+    pub simba: String,
+    #[serde(default)]
+    pub wasplib: String,
+    /// PID of the launcher instance watching the script, which finishes it once it exits.
+    #[serde(default)]
+    pub owner: Option<u32>,
+}
+
+impl ScriptRecord {
+    pub fn new(
+        id: u32,
+        pid: u32,
+        script_id: String,
+        log_path: PathBuf,
+        memory_limit: Option<u64>,
+        client: Option<WindowMatch>,
+    ) -> Self {
+        Self {
+            id,
+            pid,
+            started: process_start_time(pid).unwrap_or_else(unix_now),
+            script_id,
+            log_path,
+            memory_limit,
+            client,
+            tags: Vec::new(),
+            simba: String::new(),
+            wasplib: String::new(),
+            owner: Some(std::process::id()),
+        }
+    }
+
+    /// Whether a launcher instance is still watching the script.
+    pub fn is_owned(&self) -> bool {
+        self.owner.is_some_and(is_running)
+    }
+
+    fn is_alive(&self) -> bool {
+        if !is_running(self.pid) {
+            return false;
+        }
+
+        match process_start_time(self.pid) {
+            Some(started) => started.abs_diff(self.started) <= START_TIME_TOLERANCE,
+            None => false,
+        }
+    }
+}
+
+/// Scripts that are running, persisted so another launcher instance (the GUI, a CLI
+/// run) can list, stop or re-adopt them. Every change re-reads the file under a lock
+/// file, so instances writing at the same time don't drop each other's records.
+#[derive(Default)]
+pub struct ScriptRegistry {
+    path: PathBuf,
+    records: HashMap<u32, ScriptRecord>,
+}
+
+impl ScriptRegistry {
+    pub fn load(path: PathBuf) -> Self {
+        let records = read_records(&path);
+        Self { path, records }
+    }
+
+    pub fn records(&self) -> Vec<ScriptRecord> {
+        let mut records: Vec<ScriptRecord> = self.records.values().cloned().collect();
+        records.sort_by_key(|r| r.started);
+        records
+    }
+
+    pub fn insert(&mut self, record: ScriptRecord) {
+        self.update(|records| {
+            records.insert(record.id, record);
+            true
+        });
+    }
+
+    pub fn remove(&mut self, id: u32) -> Option<ScriptRecord> {
+        let mut removed = None;
+        self.update(|records| {
+            removed = records.remove(&id);
+            removed.is_some()
+        });
+        removed
+    }
+
+    /// Adds `tag` to the record of script `id`, returns false if there is none.
+    pub fn tag(&mut self, id: u32, tag: &str) -> bool {
+        let mut found = false;
+        self.update(|records| {
+            let Some(record) = records.get_mut(&id) else {
+                return false;
+            };
+            found = true;
+            if record.tags.iter().any(|t| t == tag) {
+                return false;
+            }
+            record.tags.push(tag.to_string());
+            true
+        });
+        found
+    }
+
+    /// Drops the records of processes that are gone and returns the ones still running.
+    /// Records with a live owner are left for it to finish.
+    pub fn prune(&mut self) -> Vec<ScriptRecord> {
+        self.update(|records| {
+            let before = records.len();
+            records.retain(|_, record| record.is_alive() || record.is_owned());
+            records.len() != before
+        });
+        let mut records = self.records();
+        records.retain(ScriptRecord::is_alive);
+        records
+    }
+
+    /// Takes over the running scripts no live launcher instance watches.
+    pub fn adopt(&mut self) -> Vec<ScriptRecord> {
+        let mut adopted = Vec::new();
+        self.update(|records| {
+            let before = records.len();
+            records.retain(|_, record| record.is_alive() || record.is_owned());
+            for record in records.values_mut().filter(|r| !r.is_owned()) {
+                record.owner = Some(std::process::id());
+                adopted.push(record.clone());
+            }
+            records.len() != before || !adopted.is_empty()
+        });
+        adopted.sort_by_key(|r| r.started);
+        adopted
+    }
+
+    /// Reloads the records from disk, applies `change` and writes them back if it
+    /// returns true, all while holding the lock file.
+    fn update(&mut self, change: impl FnOnce(&mut HashMap<u32, ScriptRecord>) -> bool) {
+        if self.path.as_os_str().is_empty() {
+            change(&mut self.records);
+            return;
+        }
+
+        if let Some(parent) = self.path.parent() {
+            let _ = create_dir_all(parent);
+        }
+        let lock = File::create(self.path.with_extension("lock")).and_then(|file| {
+            file.lock()?;
+            Ok(file)
+        });
+        if let Err(e) = &lock {
+            eprintln!("Failed to lock script registry: {}", e);
+        }
+
+        self.records = read_records(&self.path);
+        if change(&mut self.records) {
+            self.save();
+        }
+    }
+
+    fn save(&self) {
+        match serde_json::to_string_pretty(&self.records()) {
+            Ok(data) => {
+                if let Err(e) = fs::write(&self.path, data) {
+                    eprintln!("Failed to save script registry: {}", e);
+                }
+            }
+            Err(e) => eprintln!("Failed to serialize script registry: {}", e),
+        }
+    }
+}
+
+fn read_records(path: &Path) -> HashMap<u32, ScriptRecord> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|data| serde_json::from_str::<Vec<ScriptRecord>>(&data).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|record| (record.id, record))
+        .collect()
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Rebuilds `LauncherVariables::scripts` from the records of scripts that outlived
+/// the previous launcher instance.
+pub fn readopt_scripts(app: &tauri::AppHandle) {
+    let Some(launcher) = app.try_state::<Mutex<LauncherVariables>>() else {
+        return;
+    };
+
+    let adopted = {
+        let guard = launcher.lock().unwrap();
+        let records = guard.registry.lock().unwrap().adopt();
+        let mut scripts = guard.scripts.lock().unwrap();
+        let mut bindings = guard.bindings.lock().unwrap();
+        let queue = guard.queue.clone();
+
+        records
+            .into_iter()
+            .map(|record| {
+                println!(
+                    "Re-adopting script {} (PID: {}, ID: {})",
+                    record.script_id, record.pid, record.id
+                );
+                let process: SharedProcess =
+                    Arc::new(Mutex::new(Some(ScriptProcess::Adopted(record.pid))));
+                scripts.insert(record.id, process.clone());
+                queue.claim(record.id);
+                if let Some(client) = &record.client {
+                    bindings.insert(record.id, client.clone());
+                }
+                (record.id, process, record.memory_limit)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    for (id, process, memory_limit) in adopted {
+        watch_script(app.clone(), id, process, memory_limit);
+    }
+    refresh_tray(app);
+}
+
+/// Forgets a script that exited or was killed, records the run and tells the frontend
+/// about it.
+pub fn finish_script(app: &tauri::AppHandle, id: u32, exit: ScriptExit) {
+    println!("Process {} exited with {}", id, exit);
+    METRICS.script_exited(exit.reason, exit.code);
+
+    let finished = app
+        .try_state::<Mutex<LauncherVariables>>()
+        .and_then(|launcher_state| {
+            let guard = launcher_state.lock().unwrap();
+            guard.scripts.lock().unwrap().remove(&id);
+            let request = guard.launches.lock().unwrap().remove(&id);
+            let progress = guard.progress.lock().unwrap().remove(&id);
+            guard.bindings.lock().unwrap().remove(&id);
+            guard.queue.release(id);
+            let record = guard.registry.lock().unwrap().remove(id)?;
+
+            let run = RunRecord::new(&record, request.as_ref(), progress, exit);
+            let history = guard.history.lock().unwrap().clone();
+            Some((record, run, history))
+        });
+    let record = finished.map(|(record, run, history)| {
+        if let Err(e) = history.append(&run) {
+            eprintln!("Failed to save run history: {}", e);
+        }
+        record
+    });
+    let event = ScriptEvent::exited(id, record.as_ref(), exit);
+    refresh_tray(app);
+    notify_desktop(app, &event);
+    notify(app, event);
+
+    let _ = app.emit(
+        "script-exit",
+        json!({ "id": id, "code": exit.code, "reason": exit.reason }),
+    );
+    let _ = app.emit("process-finished", id);
+}
+
+pub fn stop_script(app: &tauri::AppHandle, id: u32, reason: ExitReason) -> Result<String, String> {
+    let launcher = app.try_state::<Mutex<LauncherVariables>>();
+    let handle = launcher.as_ref().and_then(|launcher| {
+        let launcher_guard = launcher.lock().unwrap();
+        let scripts_guard = launcher_guard.scripts.lock().unwrap();
+        let handle = scripts_guard.get(&id).cloned();
+        if handle.is_none() {
+            // Between the stop and the relaunch of a restart there is no process to kill.
+            if let Some(cancelled) = launcher_guard.restarting.lock().unwrap().get_mut(&id) {
+                *cancelled = true;
+                return None;
+            }
+        }
+        handle
+    });
+    if handle.is_none() && restart_cancelled(app, id) {
+        return Ok(format!("Restart of script {} cancelled", id));
+    }
+
+    if let Some(shared_process) = handle {
+        let mut process_guard = shared_process.lock().unwrap();
+
+        if let Some(mut process) = process_guard.take() {
+            let result = process.kill().map_err(|e| e.to_string());
+
+            finish_script(app, id, ScriptExit { code: None, reason });
+
+            match result {
+                Ok(_) => Ok(format!("Process {} killed", id)),
+                Err(e) => Err(format!("Failed to kill: {}", e)),
+            }
+        } else {
+            Err(format!("Process {} is already stopping or finished", id))
+        }
+    } else {
+        Err(format!("No active script found for ID {}", id))
+    }
+}
+
+/// Everything needed to start a script again with the same settings.
+#[derive(Clone)]
+pub struct LaunchRequest {
+    /// See `new_script_id`, kept across restarts.
+    pub id: u32,
+    pub args: Vec<String>,
+    /// Client slot, `DEFAULT_CLIENT` when unset.
+    pub client: Option<String>,
+    pub profile: Option<String>,
+    /// Launch even if another script is bound to the same client.
+    pub force: bool,
+    pub channel: Channel<String>,
+    pub restarts: u32,
+}
+
+/// Random script ID that is neither in `used` nor 0.
+fn unused_id(used: impl Fn(u32) -> bool) -> u32 {
+    loop {
+        let id = getrandom::u32().expect("OS random number generator is unavailable");
+        if id != 0 && !used(id) {
+            return id;
+        }
+    }
+}
+
+/// Picks the ID of a new script. IDs are shared by every launcher instance through the
+/// registry, so they are random rather than counted per process.
+pub fn new_script_id(app: &tauri::AppHandle) -> u32 {
+    let launcher = app.state::<Mutex<LauncherVariables>>();
+    let guard = launcher.lock().unwrap();
+    let records = guard.registry.lock().unwrap().prune();
+    let scripts = guard.scripts.lock().unwrap();
+    let launches = guard.launches.lock().unwrap();
+    unused_id(|id| {
+        scripts.contains_key(&id)
+            || launches.contains_key(&id)
+            || records.iter().any(|r| r.id == id)
+    })
+}
+
+/// Binds script `id` to the client in `slot`. Fails if the slot is empty, its client is
+/// gone or, unless `force` is set, another script already targets the same window. That
+/// includes scripts of other launcher instances, found through their `running` records.
+fn bind_client(
+    provider: &dyn ClientProvider,
+    clients: &HashMap<String, WindowMatch>,
+    bindings: &mut HashMap<u32, WindowMatch>,
+    running: &[ScriptRecord],
+    id: u32,
+    slot: &str,
+    force: bool,
+) -> Result<WindowMatch, String> {
+    let Some(client) = clients.get(slot).cloned() else {
+        return Err(format!("Client {} is null", slot));
+    };
+    if !provider.is_alive(&client) {
+        return Err(format!("Client {} is no longer running", slot));
+    }
+
+    if !force {
+        if let Some((other, _)) = bindings.iter().find(|(_, c)| c.hwnd == client.hwnd) {
+            return Err(format!("Client is already in use by script {}", other));
+        }
+        let elsewhere = running
+            .iter()
+            .find(|r| r.id != id && r.client.as_ref().is_some_and(|c| c.hwnd == client.hwnd));
+        if let Some(other) = elsewhere {
+            return Err(format!("Client is already in use by script {}", other.id));
+        }
+    }
+    bindings.insert(id, client.clone());
+    Ok(client)
+}
+
+/// Starts a script and everything watching it. A restarted script keeps its ID and
+/// output channel.
+pub async fn launch_script(app: &tauri::AppHandle, request: LaunchRequest) -> Result<u32, String> {
+    let id = request.id;
+    let script_id = request.args.get(3).cloned().unwrap_or_default();
+    let settings = app.state::<SettingsStore>().get();
+    let triggers = triggers::compile(&settings.triggers, &script_id)?;
+    let profile = match &request.profile {
+        Some(name) => match settings.profiles.get(name) {
+            Some(profile) => profile.clone(),
+            None => return Err(format!("Unknown launch profile: {}", name)),
+        },
+        None => LaunchProfile::default(),
+    };
+    let launcher = app.state::<Mutex<LauncherVariables>>();
+    let provider = app.state::<Box<dyn ClientProvider>>();
+
+    let (simba_path, logs_path, client, profile, heartbeat, queue) = {
+        let guard = launcher.lock().unwrap();
+        let heartbeat = match &profile.watchdog.heartbeat {
+            Some(pattern) => Some(
+                Regex::new(pattern)
+                    .map_err(|e| format!("Invalid heartbeat pattern {:?}: {}", pattern, e))?,
+            ),
+            None => None,
+        };
+
+        let slot = request.client.as_deref().unwrap_or(DEFAULT_CLIENT);
+        let running = guard.registry.lock().unwrap().prune();
+        let client = bind_client(
+            provider.as_ref(),
+            &guard.clients,
+            &mut guard.bindings.lock().unwrap(),
+            &running,
+            id,
+            slot,
+            request.force,
+        )?;
+
+        (
+            guard.simba.clone(),
+            guard.logs.clone(),
+            client,
+            profile,
+            heartbeat,
+            guard.queue.clone(),
+        )
+    };
+
+    let log_path = logs_path.join(format!("{}.log", id));
+    let (trigger_app, progress_app) = (app.clone(), app.clone());
+    let output = Arc::new(
+        ScriptOutput::new(heartbeat)
+            .with_triggers(
+                triggers,
+                Box::new(move |trigger, line| fire(&trigger_app, id, trigger, line)),
+            )
+            .with_progress(Box::new(move |report| {
+                report_progress(&progress_app, id, report)
+            })),
+    );
+
+    if !queue.acquire(id).await {
+        let guard = launcher.lock().unwrap();
+        guard.bindings.lock().unwrap().remove(&id);
+        return Err(format!("Launch of script {} was cancelled", id));
+    }
+    let run = match run_simba_script(
+        simba_path,
+        client.hwnd,
+        request.args.clone(),
+        &profile.limits,
+        &log_path,
+    )
+    .await
+    {
+        Ok(run) => run,
+        Err(e) => {
+            queue.release(id);
+            let guard = launcher.lock().unwrap();
+            guard.bindings.lock().unwrap().remove(&id);
+            return Err(e);
+        }
+    };
+    let record = ScriptRecord {
+        simba: run.simba,
+        wasplib: run.wasplib,
+        ..ScriptRecord::new(
+            id,
+            run.process.id(),
+            script_id,
+            log_path,
+            profile.limits.memory,
+            Some(client),
+        )
+    };
+
+    let channel = request.channel.clone();
+    let shared_process = Arc::new(Mutex::new(Some(ScriptProcess::Spawned(run.process))));
+    let mut event = ScriptEvent::started(&record);
+    if request.restarts > 0 {
+        event.event = EventKind::Restarted;
+        event.details = format!("restart {}", request.restarts);
+        METRICS.script_restarted();
+        let _ = app.emit(
+            "script-restarted",
+            json!({ "id": id, "restarts": request.restarts }),
+        );
+    }
+
+    {
+        let guard = launcher.lock().unwrap();
+        guard
+            .scripts
+            .lock()
+            .unwrap()
+            .insert(id, shared_process.clone());
+        guard.registry.lock().unwrap().insert(record);
+        guard.launches.lock().unwrap().insert(id, request);
+    }
+    // Triggers act on the script through the launcher, so only start reading now.
+    run.pipes.forward(channel, output.clone());
+
+    METRICS.script_started();
+    notify_desktop(app, &event);
+    notify(app, event);
+    refresh_tray(app);
+    watch_script(
+        app.clone(),
+        id,
+        shared_process.clone(),
+        profile.limits.memory,
+    );
+    start_watchdog(app.clone(), id, shared_process, output, profile.watchdog);
+
+    Ok(id)
+}
+
+/// Restart count and delay for the next restart of a launch that was restarted
+/// `restarts` times and whose last run lasted `ran` seconds. `None` once the launch
+/// ran out of restarts.
+fn next_restart(restarts: u32, ran: Option<u64>) -> Option<(u32, Duration)> {
+    let restarts = match ran {
+        Some(ran) if ran >= STABLE_RUN => 1,
+        _ => restarts + 1,
+    };
+    if restarts > MAX_RESTARTS {
+        return None;
+    }
+    Some((restarts, RESTART_DELAY * 2u32.pow(restarts - 1)))
+}
+
+/// Stops script `id` and, after a backoff, launches it again with the request it was
+/// started with. Fails without doing anything while a restart of `id` is pending.
+pub async fn restart_script(
+    app: &tauri::AppHandle,
+    id: u32,
+    reason: ExitReason,
+) -> Result<u32, String> {
+    let launcher = app
+        .try_state::<Mutex<LauncherVariables>>()
+        .ok_or_else(|| format!("Script {} was not started by this launcher", id))?;
+    let (request, started) = {
+        let guard = launcher.lock().unwrap();
+        let request = guard.launches.lock().unwrap().get(&id).cloned();
+        let Some(request) = request else {
+            return Err(format!("Script {} was not started by this launcher", id));
+        };
+        let mut restarting = guard.restarting.lock().unwrap();
+        if restarting.contains_key(&id) {
+            return Err(format!("Script {} is already restarting", id));
+        }
+        restarting.insert(id, false);
+        drop(restarting);
+        let records = guard.registry.lock().unwrap().records();
+        let started = records.iter().find(|r| r.id == id).map(|r| r.started);
+        (request, started)
+    };
+
+    let result = async {
+        stop_script(app, id, reason)?;
+
+        let ran = started.map(|started| unix_now().saturating_sub(started));
+        let Some((restarts, delay)) = next_restart(request.restarts, ran) else {
+            return Err(format!(
+                "Script {} was restarted {} times, leaving it stopped",
+                id, MAX_RESTARTS
+            ));
+        };
+        println!("Restarting script {} in {}s", id, delay.as_secs());
+        tokio::time::sleep(delay).await;
+        if restart_cancelled(app, id) {
+            return Err(format!("Restart of script {} was cancelled", id));
+        }
+
+        let id = launch_script(
+            app,
+            LaunchRequest {
+                restarts,
+                ..request
+            },
+        )
+        .await?;
+        // A stop that came in while launching had nothing to kill yet.
+        if restart_cancelled(app, id) {
+            stop_script(app, id, ExitReason::Killed)?;
+            return Err(format!("Restart of script {} was cancelled", id));
+        }
+        Ok(id)
+    }
+    .await;
+
+    let guard = launcher.lock().unwrap();
+    guard.restarting.lock().unwrap().remove(&id);
+    result
+}
+
+fn restart_cancelled(app: &tauri::AppHandle, id: u32) -> bool {
+    app.try_state::<Mutex<LauncherVariables>>()
+        .is_some_and(|launcher| {
+            let guard = launcher.lock().unwrap();
+            let restarting = guard.restarting.lock().unwrap();
+            restarting.get(&id).copied().unwrap_or(false)
+        })
+}
+
+fn exceeds_memory(sampler: &dyn ProcessSampler, pid: u32, limit: Option<u64>) -> bool {
+    match limit {
+        Some(limit) => sampler
+            .sample(pid)
+            .map(|sample| sample.memory > limit)
+            .unwrap_or(false),
+        None => false,
+    }
+}
+
+pub fn watch_script(
+    app: tauri::AppHandle,
+    id: u32,
+    shared_process: SharedProcess,
+    memory_limit: Option<u64>,
+) {
+    std::thread::spawn(move || {
+        let sampler = platform_sampler();
+
+        loop {
+            let status = {
+                let mut inner_guard = shared_process.lock().unwrap();
+                let Some(process) = inner_guard.as_mut() else {
+                    return;
+                };
+
+                if exceeds_memory(sampler.as_ref(), process.pid(), memory_limit) {
+                    let _ = process.kill();
+                    inner_guard.take();
+                    Ok(Some(ScriptExit {
+                        code: None,
+                        reason: ExitReason::MemoryLimit,
+                    }))
+                } else {
+                    process.try_wait()
+                }
+            };
+
+            match status {
+                Ok(Some(exit)) => {
+                    finish_script(&app, id, exit);
+                    break;
+                }
+                Ok(None) => {
+                    std::thread::sleep(Duration::from_millis(500));
+                }
+                Err(e) => {
+                    println!("Error checking process status: {}", e);
+                    break;
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::client::MockProvider;
+
+    fn record(id: u32) -> ScriptRecord {
+        ScriptRecord {
+            id,
+            pid: std::process::id(),
+            started: process_start_time(std::process::id()).unwrap_or_else(unix_now),
+            script_id: format!("script{}", id),
+            log_path: PathBuf::from(format!("{}.log", id)),
+            memory_limit: None,
+            client: None,
+            tags: Vec::new(),
+            simba: String::new(),
+            wasplib: String::new(),
+            owner: Some(std::process::id()),
+        }
+    }
+
+    // No process has this PID.
+    const DEAD_PID: u32 = u32::MAX;
+
+    #[test]
+    fn restarts_back_off_and_give_up() {
+        assert_eq!(next_restart(0, Some(5)), Some((1, Duration::from_secs(2))));
+        assert_eq!(next_restart(1, Some(5)), Some((2, Duration::from_secs(4))));
+        assert_eq!(next_restart(4, None), Some((5, Duration::from_secs(32))));
+        assert_eq!(next_restart(MAX_RESTARTS, Some(5)), None);
+
+        // A long healthy run starts the count over.
+        assert_eq!(
+            next_restart(MAX_RESTARTS, Some(STABLE_RUN)),
+            Some((1, RESTART_DELAY))
+        );
+    }
+
+    #[test]
+    fn registries_sharing_a_file_keep_each_others_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scripts.json");
+        let mut gui = ScriptRegistry::load(path.clone());
+        let mut cli = ScriptRegistry::load(path.clone());
+
+        gui.insert(record(1));
+        cli.insert(record(2));
+        assert!(gui.tag(2, "banked"));
+        assert!(cli.remove(1).is_some());
+
+        let ids: Vec<u32> = ScriptRegistry::load(path)
+            .records()
+            .iter()
+            .map(|r| r.id)
+            .collect();
+        assert_eq!(ids, vec![2]);
+        assert_eq!(gui.prune()[0].tags, vec!["banked"]);
+    }
+
+    #[test]
+    fn leaves_records_to_their_live_owner() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut registry = ScriptRegistry::load(dir.path().join("scripts.json"));
+
+        // Process gone, but its owner will finish it.
+        registry.insert(ScriptRecord {
+            pid: DEAD_PID,
+            ..record(1)
+        });
+        // Running, owner gone.
+        registry.insert(ScriptRecord {
+            owner: Some(DEAD_PID),
+            ..record(2)
+        });
+        // Process and owner gone.
+        registry.insert(ScriptRecord {
+            pid: DEAD_PID,
+            owner: Some(DEAD_PID),
+            ..record(3)
+        });
+
+        let running: Vec<u32> = registry.prune().iter().map(|r| r.id).collect();
+        assert_eq!(running, vec![2]);
+        let mut kept: Vec<u32> = registry.records().iter().map(|r| r.id).collect();
+        kept.sort();
+        assert_eq!(kept, vec![1, 2]);
+
+        let adopted = registry.adopt();
+        assert_eq!(adopted.len(), 1);
+        assert_eq!(adopted[0].id, 2);
+        assert_eq!(adopted[0].owner, Some(std::process::id()));
+        assert!(registry.adopt().is_empty());
+    }
+
+    #[test]
+    fn new_ids_avoid_used_ones() {
+        let used = HashSet::from([1, 2, 3]);
+        for _ in 0..100 {
+            let id = unused_id(|id| used.contains(&id));
+            assert!(id != 0 && !used.contains(&id));
+        }
+    }
+
+    fn slots(provider: &MockProvider) -> HashMap<String, WindowMatch> {
+        provider
+            .clients()
+            .into_iter()
+            .enumerate()
+            .map(|(i, client)| (format!("slot{}", i + 1), client))
+            .collect()
+    }
+
+    #[test]
+    fn binds_each_client_to_one_script() {
+        let provider = MockProvider::with_clients(2);
+        let clients = slots(&provider);
+        let mut bindings = HashMap::new();
+
+        let client =
+            bind_client(&provider, &clients, &mut bindings, &[], 1, "slot1", false).unwrap();
+        assert_eq!(client.hwnd, clients["slot1"].hwnd);
+        assert!(bind_client(&provider, &clients, &mut bindings, &[], 2, "slot2", false).is_ok());
+
+        let err =
+            bind_client(&provider, &clients, &mut bindings, &[], 3, "slot1", false).unwrap_err();
+        assert!(err.contains("script 1"), "{}", err);
+        assert!(!bindings.contains_key(&3));
+    }
+
+    #[test]
+    fn respects_clients_bound_by_other_instances() {
+        let provider = MockProvider::with_clients(1);
+        let clients = slots(&provider);
+        let mut bindings = HashMap::new();
+        let running = [ScriptRecord {
+            client: Some(clients["slot1"].clone()),
+            ..record(9)
+        }];
+
+        let err = bind_client(
+            &provider,
+            &clients,
+            &mut bindings,
+            &running,
+            1,
+            "slot1",
+            false,
+        )
+        .unwrap_err();
+        assert!(err.contains("script 9"), "{}", err);
+        // A restart of script 9 finds its own record.
+        assert!(bind_client(
+            &provider,
+            &clients,
+            &mut bindings,
+            &running,
+            9,
+            "slot1",
+            false
+        )
+        .is_ok());
+        assert!(bind_client(
+            &provider,
+            &clients,
+            &mut bindings,
+            &running,
+            2,
+            "slot1",
+            true
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn force_shares_a_bound_client() {
+        let provider = MockProvider::with_clients(1);
+        let clients = slots(&provider);
+        let mut bindings = HashMap::new();
+
+        bind_client(&provider, &clients, &mut bindings, &[], 1, "slot1", false).unwrap();
+        bind_client(&provider, &clients, &mut bindings, &[], 2, "slot1", true).unwrap();
+        assert_eq!(bindings[&1].hwnd, bindings[&2].hwnd);
+    }
+
+    #[test]
+    fn rejects_empty_slots_and_closed_clients() {
+        let provider = MockProvider::with_clients(1);
+        let clients = slots(&provider);
+        let mut bindings = HashMap::new();
+
+        assert!(bind_client(
+            &provider,
+            &clients,
+            &mut bindings,
+            &[],
+            1,
+            DEFAULT_CLIENT,
+            false
+        )
+        .is_err());
+
+        provider.remove_client(clients["slot1"].hwnd);
+        let err =
+            bind_client(&provider, &clients, &mut bindings, &[], 1, "slot1", false).unwrap_err();
+        assert!(err.contains("no longer running"), "{}", err);
+        assert!(bindings.is_empty());
+    }
+}
@@ -65,8 +65,7 @@ pub fn handle_client(mut stream: TcpStream, app: tauri::AppHandle) -> bool {
         "error": error
     });
 
-    let _ = app
-        .emit("oauth-callback", payload)
+    app.emit("oauth-callback", payload)
         .expect("Failed to ping the front-end!");
 
     true
@@ -0,0 +1,150 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use super::{
+    migrations::{migrate, version_of, SCHEMA_VERSION},
+    Settings,
+};
+use crate::{client::ClientMatcher, triggers::Trigger};
+
+// Machine specific, every machine keeps its own.
+const LOCAL_KEYS: &[&str] = &["paths"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingChange {
+    pub key: String,
+    pub current: Value,
+    pub incoming: Value,
+}
+
+/// Settings as they get shared with other machines: executable paths are left out
+/// and so are secrets: account environment variables usually hold credentials and
+/// webhook URLs embed their token.
+pub fn export(settings: &Settings) -> Result<String, String> {
+    let mut settings = settings.clone();
+    for account in settings.accounts.values_mut() {
+        account.env.clear();
+    }
+    settings.control.token.clear();
+    for webhook in settings.webhooks.iter_mut() {
+        webhook.url.clear();
+    }
+
+    let mut map = settings.to_map();
+    for key in LOCAL_KEYS {
+        map.remove(*key);
+    }
+
+    serde_json::to_string_pretty(&map).map_err(|e| e.to_string())
+}
+
+/// Parses and validates a bundle, upgrading it first if it came from an older
+/// launcher. Keys missing from the bundle keep their value from `current`.
+pub fn import(current: &Settings, data: &str) -> Result<Settings, String> {
+    let mut bundle: Map<String, Value> =
+        serde_json::from_str(data).map_err(|e| format!("Invalid settings bundle: {}", e))?;
+
+    let version = version_of(&bundle);
+    if version > SCHEMA_VERSION {
+        return Err(format!(
+            "Settings bundle version {} is newer than this launcher supports ({}).",
+            version, SCHEMA_VERSION
+        ));
+    }
+    migrate(&mut bundle);
+
+    let mut merged = current.to_map();
+    for (key, value) in bundle {
+        if !merged.contains_key(&key) {
+            return Err(format!("Unknown setting: {}", key));
+        }
+        if !LOCAL_KEYS.contains(&key.as_str()) {
+            merged.insert(key, value);
+        }
+    }
+
+    let mut settings: Settings = serde_json::from_value(Value::Object(merged))
+        .map_err(|e| format!("Invalid settings bundle: {}", e))?;
+    ClientMatcher::new(&settings.client_rules)?;
+    for profile in settings.profiles.values() {
+        profile.validate()?;
+    }
+    for rule in &settings.triggers {
+        Trigger::new(rule.clone())?;
+    }
+
+    // Exported bundles carry no secrets, keep the ones this machine already has.
+    if settings.control.token.is_empty() {
+        settings.control.token = current.control.token.clone();
+    }
+    for webhook in settings.webhooks.iter_mut() {
+        if webhook.url.is_empty() {
+            if let Some(local) = current.webhooks.iter().find(|w| w.name == webhook.name) {
+                webhook.url = local.url.clone();
+            }
+        }
+    }
+    for (name, account) in settings.accounts.iter_mut() {
+        if account.env.is_empty() {
+            if let Some(local) = current.accounts.get(name) {
+                account.env = local.env.clone();
+            }
+        }
+    }
+
+    Ok(settings)
+}
+
+/// Top-level keys whose value would change by replacing `current` with `incoming`.
+pub fn diff(current: &Settings, incoming: &Settings) -> Vec<SettingChange> {
+    let current = current.to_map();
+    let mut changes: Vec<SettingChange> = incoming
+        .to_map()
+        .into_iter()
+        .filter(|(key, value)| current.get(key) != Some(value))
+        .map(|(key, incoming)| SettingChange {
+            current: current.get(&key).cloned().unwrap_or(Value::Null),
+            key,
+            incoming,
+        })
+        .collect();
+
+    changes.sort_by(|a, b| a.key.cmp(&b.key));
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn rejects_bundles_from_newer_launchers() {
+        let bundle = json!({ "version": SCHEMA_VERSION + 1 }).to_string();
+        let err = import(&Settings::default(), &bundle).unwrap_err();
+        assert!(err.contains("newer"), "{}", err);
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        let bundle = json!({ "version": SCHEMA_VERSION, "colour": "blue" }).to_string();
+        assert_eq!(
+            import(&Settings::default(), &bundle).unwrap_err(),
+            "Unknown setting: colour"
+        );
+    }
+
+    #[test]
+    fn upgrades_old_bundles_and_keeps_local_paths() {
+        let mut current = Settings::default();
+        current
+            .paths
+            .insert("simba".to_string(), "C:/Simba/Simba.exe".into());
+        let bundle = json!({ "devmode": true, "paths": { "simba": "C:/elsewhere.exe" } });
+
+        let settings = import(&current, &bundle.to_string()).unwrap();
+        assert!(settings.devmode);
+        assert_eq!(settings.paths, current.paths);
+    }
+}
@@ -0,0 +1,103 @@
+use serde_json::{Map, Value};
+
+type Migration = fn(&mut Map<String, Value>);
+
+/// Upgrade steps, `MIGRATIONS[n]` takes a file from version `n` to `n + 1`.
+/// Only ever append to this list.
+const MIGRATIONS: &[Migration] = &[normalize_paths];
+
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// Version of a raw settings file. Files written before versioning have no key and
+/// count as version 0.
+pub fn version_of(entries: &Map<String, Value>) -> u32 {
+    entries
+        .get("version")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32)
+        .unwrap_or(0)
+}
+
+/// Runs every migration newer than the file's version and stamps the result with
+/// `SCHEMA_VERSION`. Files from a newer launcher are left untouched.
+pub fn migrate(entries: &mut Map<String, Value>) -> bool {
+    let version = version_of(entries);
+    if version >= SCHEMA_VERSION {
+        return false;
+    }
+
+    for (step, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        println!("Migrating settings from version {} to {}", step, step + 1);
+        migration(entries);
+    }
+
+    entries.insert("version".to_string(), Value::from(SCHEMA_VERSION));
+    true
+}
+
+// v0 -> v1: `set_executable_path` used to replace `paths` with a single
+// `{exe: path}` object, sometimes holding unknown keys or empty strings. Keep only
+// the usable entries so the typed settings can load them.
+fn normalize_paths(entries: &mut Map<String, Value>) {
+    let paths = match entries.remove("paths") {
+        Some(Value::Object(paths)) => paths
+            .into_iter()
+            .filter(|(exe, path)| {
+                matches!(exe.as_str(), "simba" | "devsimba" | "runelite" | "osclient")
+                    && path.as_str().is_some_and(|p| !p.is_empty())
+            })
+            .collect(),
+        _ => Map::new(),
+    };
+
+    entries.insert("paths".to_string(), Value::Object(paths));
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn map(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(map) => map,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn upgrades_unversioned_files() {
+        let mut entries = map(json!({
+            "devmode": true,
+            "paths": { "simba": "C:/Simba/Simba.exe", "runelite": "", "notepad": "C:/notepad.exe" },
+        }));
+        assert_eq!(version_of(&entries), 0);
+
+        assert!(migrate(&mut entries));
+        assert_eq!(version_of(&entries), SCHEMA_VERSION);
+        assert_eq!(entries["devmode"], json!(true));
+        assert_eq!(entries["paths"], json!({ "simba": "C:/Simba/Simba.exe" }));
+
+        // Already current, nothing left to do.
+        assert!(!migrate(&mut entries));
+    }
+
+    #[test]
+    fn normalize_paths_replaces_invalid_values() {
+        let mut entries = map(json!({ "paths": "C:/Simba/Simba.exe" }));
+        normalize_paths(&mut entries);
+        assert_eq!(entries["paths"], json!({}));
+
+        let mut entries = Map::new();
+        normalize_paths(&mut entries);
+        assert_eq!(entries["paths"], json!({}));
+    }
+
+    #[test]
+    fn leaves_newer_files_alone() {
+        let mut entries = map(json!({ "version": SCHEMA_VERSION + 1, "paths": "kept" }));
+        assert!(!migrate(&mut entries));
+        assert_eq!(entries["paths"], json!("kept"));
+    }
+}
@@ -0,0 +1,191 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::Wry;
+use tauri_plugin_store::Store;
+
+use crate::{
+    client::{MatchRules, WindowLayout},
+    control::ControlSettings,
+    metrics::MetricsSettings,
+    profiles::{ClientAccount, LaunchProfile},
+    triggers::TriggerRule,
+    webhooks::Webhook,
+};
+
+mod bundle;
+mod migrations;
+
+pub use self::bundle::SettingChange;
+
+use self::migrations::{migrate, version_of, SCHEMA_VERSION};
+
+/// Everything the launcher persists in `settings.json`. Each field is a top-level
+/// key of the store and missing keys fall back to their defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Schema version of the file, see `migrations`.
+    pub version: u32,
+    /// User overrides for executable paths, keyed like `get_executable_path`'s `exe`.
+    pub paths: HashMap<String, PathBuf>,
+    pub devmode: bool,
+    pub dev_updates: bool,
+    pub stop_on_client_lost: bool,
+    pub max_scripts: usize,
+    pub profiles: HashMap<String, LaunchProfile>,
+    pub accounts: HashMap<String, ClientAccount>,
+    pub layouts: HashMap<String, WindowLayout>,
+    pub client_rules: MatchRules,
+    pub client_labels: HashMap<String, String>,
+    pub control: ControlSettings,
+    pub metrics: MetricsSettings,
+    pub webhooks: Vec<Webhook>,
+    /// Desktop notifications when a script stops.
+    pub notifications: bool,
+    /// Rules acting on script output, see `triggers`.
+    pub triggers: Vec<TriggerRule>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            paths: HashMap::new(),
+            devmode: false,
+            dev_updates: true,
+            stop_on_client_lost: false,
+            max_scripts: 0,
+            profiles: HashMap::new(),
+            accounts: HashMap::new(),
+            layouts: HashMap::new(),
+            client_rules: MatchRules::default(),
+            client_labels: HashMap::new(),
+            control: ControlSettings::default(),
+            metrics: MetricsSettings::default(),
+            webhooks: Vec::new(),
+            notifications: true,
+            triggers: Vec::new(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn path(&self, exe: &str) -> Option<PathBuf> {
+        self.paths.get(exe).cloned()
+    }
+
+    fn to_map(&self) -> Map<String, Value> {
+        match serde_json::to_value(self) {
+            Ok(Value::Object(map)) => map,
+            _ => Map::new(),
+        }
+    }
+
+    /// Builds the settings from raw store entries. Keys are applied one at a time so a
+    /// single malformed value only resets that key instead of the whole file.
+    fn from_entries(entries: Map<String, Value>) -> Self {
+        let mut merged = Settings::default().to_map();
+
+        for (key, value) in entries {
+            if !merged.contains_key(&key) {
+                continue;
+            }
+
+            let mut candidate = merged.clone();
+            candidate.insert(key.clone(), value);
+            if serde_json::from_value::<Settings>(Value::Object(candidate.clone())).is_ok() {
+                merged = candidate;
+            } else {
+                println!("Ignoring invalid setting {:?}, using the default.", key);
+            }
+        }
+
+        serde_json::from_value(Value::Object(merged)).unwrap_or_default()
+    }
+}
+
+/// Owns the typed settings and is the only place that writes to `settings.json`.
+pub struct SettingsStore {
+    store: Arc<Store<Wry>>,
+    settings: Mutex<Settings>,
+}
+
+impl SettingsStore {
+    /// `path` is where the store lives on disk, used to back the file up before
+    /// migrating it.
+    pub fn load(store: Arc<Store<Wry>>, path: &Path) -> Self {
+        let mut entries: Map<String, Value> = store.entries().into_iter().collect();
+
+        // A fresh install has nothing to migrate and starts at the current version.
+        if !entries.is_empty() {
+            let version = version_of(&entries);
+            let original = entries.clone();
+            if migrate(&mut entries) {
+                backup(path, version);
+                for key in original.keys().filter(|key| !entries.contains_key(*key)) {
+                    store.delete(key);
+                }
+            }
+        }
+
+        let settings = Settings::from_entries(entries);
+
+        // Write back so keys missing from older files get their defaults persisted.
+        for (key, value) in settings.to_map() {
+            store.set(key, value);
+        }
+
+        Self {
+            store,
+            settings: Mutex::new(settings),
+        }
+    }
+
+    pub fn get(&self) -> Settings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    pub fn export(&self) -> Result<String, String> {
+        bundle::export(&self.settings.lock().unwrap())
+    }
+
+    /// Returns the settings `data` would result in without applying them.
+    pub fn preview_import(&self, data: &str) -> Result<(Settings, Vec<SettingChange>), String> {
+        let current = self.get();
+        let incoming = bundle::import(&current, data)?;
+        let changes = bundle::diff(&current, &incoming);
+        Ok((incoming, changes))
+    }
+
+    /// Applies `f` to the current settings and persists only the keys it changed.
+    pub fn update<F: FnOnce(&mut Settings)>(&self, f: F) {
+        let mut settings = self.settings.lock().unwrap();
+        let before = settings.to_map();
+        f(&mut settings);
+
+        for (key, value) in settings.to_map() {
+            if before.get(&key) != Some(&value) {
+                self.store.set(key, value);
+            }
+        }
+    }
+}
+
+fn backup(path: &Path, version: u32) {
+    if !path.exists() {
+        return;
+    }
+
+    let backup = path.with_file_name(format!("settings.v{}.json.bak", version));
+    match fs::copy(path, &backup) {
+        Ok(_) => println!("Backed up settings to {}", backup.display()),
+        Err(e) => eprintln!("Failed to back up settings before migrating: {}", e),
+    }
+}
@@ -0,0 +1,198 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use tauri::{Emitter, Manager};
+
+use crate::{
+    process::{platform_sampler, ProcessSampler},
+    scripts::{unix_now, SharedProcess},
+    LauncherVariables,
+};
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScriptStats {
+    pub id: u32,
+    pub pid: u32,
+    /// CPU usage as a percentage of a single core.
+    pub cpu: f32,
+    /// Resident memory in bytes.
+    pub memory: u64,
+    /// Seconds since the script was started.
+    pub uptime: u64,
+}
+
+struct PreviousSample {
+    at: Instant,
+    cpu_time: Duration,
+}
+
+pub fn start_stats_sampler(app: tauri::AppHandle) {
+    thread::spawn(move || {
+        let sampler = platform_sampler();
+        let mut previous: HashMap<u32, PreviousSample> = HashMap::new();
+
+        loop {
+            thread::sleep(SAMPLE_INTERVAL);
+
+            let Some(launcher) = app.try_state::<Mutex<LauncherVariables>>() else {
+                continue;
+            };
+
+            let (scripts, started) = {
+                let guard = launcher.lock().unwrap();
+                let scripts: Vec<(u32, SharedProcess)> = guard
+                    .scripts
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .map(|(id, process)| (*id, process.clone()))
+                    .collect();
+                let started: HashMap<u32, u64> = guard
+                    .registry
+                    .lock()
+                    .unwrap()
+                    .records()
+                    .into_iter()
+                    .map(|r| (r.id, r.started))
+                    .collect();
+                (scripts, started)
+            };
+
+            let stats = sample_scripts(
+                sampler.as_ref(),
+                &scripts,
+                &started,
+                &mut previous,
+                Instant::now(),
+            );
+
+            {
+                let guard = launcher.lock().unwrap();
+                *guard.stats.lock().unwrap() = stats.iter().map(|s| (s.id, s.clone())).collect();
+            }
+
+            let _ = app.emit("script-stats", &stats);
+        }
+    });
+}
+
+fn sample_scripts(
+    sampler: &dyn ProcessSampler,
+    scripts: &[(u32, SharedProcess)],
+    started: &HashMap<u32, u64>,
+    previous: &mut HashMap<u32, PreviousSample>,
+    now: Instant,
+) -> Vec<ScriptStats> {
+    let mut stats = Vec::with_capacity(scripts.len());
+
+    for (id, process) in scripts {
+        let Some(pid) = process.lock().unwrap().as_ref().map(|p| p.pid()) else {
+            continue;
+        };
+        let Some(sample) = sampler.sample(pid) else {
+            continue;
+        };
+
+        let cpu = match previous.get(id) {
+            Some(prev) => {
+                let wall = now.duration_since(prev.at).as_secs_f32();
+                let used = sample.cpu_time.saturating_sub(prev.cpu_time).as_secs_f32();
+                if wall > 0.0 {
+                    used / wall * 100.0
+                } else {
+                    0.0
+                }
+            }
+            None => 0.0,
+        };
+
+        previous.insert(
+            *id,
+            PreviousSample {
+                at: now,
+                cpu_time: sample.cpu_time,
+            },
+        );
+
+        stats.push(ScriptStats {
+            id: *id,
+            pid,
+            cpu,
+            memory: sample.memory,
+            uptime: started
+                .get(id)
+                .map(|s| unix_now().saturating_sub(*s))
+                .unwrap_or(0),
+        });
+    }
+
+    previous.retain(|id, _| scripts.iter().any(|(s, _)| s == id));
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{process::MockSampler, scripts::ScriptProcess};
+
+    fn script(id: u32, pid: u32) -> (u32, SharedProcess) {
+        (id, Arc::new(Mutex::new(Some(ScriptProcess::Adopted(pid)))))
+    }
+
+    #[test]
+    fn cpu_usage_is_the_delta_between_samples() {
+        let sampler = MockSampler::default();
+        let scripts = vec![script(1, 100)];
+        let started = HashMap::from([(1, unix_now() - 60)]);
+        let mut previous = HashMap::new();
+        let start = Instant::now();
+
+        sampler.set(100, Duration::from_secs(30), 1 << 20);
+        let first = sample_scripts(&sampler, &scripts, &started, &mut previous, start);
+        assert_eq!(first[0].cpu, 0.0);
+        assert_eq!(first[0].memory, 1 << 20);
+        assert!(first[0].uptime >= 60);
+
+        // One second of CPU time over two seconds.
+        sampler.set(100, Duration::from_secs(31), 2 << 20);
+        let second = sample_scripts(
+            &sampler,
+            &scripts,
+            &started,
+            &mut previous,
+            start + Duration::from_secs(2),
+        );
+        assert!((second[0].cpu - 50.0).abs() < 0.01, "{}", second[0].cpu);
+        assert_eq!(second[0].memory, 2 << 20);
+    }
+
+    #[test]
+    fn drops_exited_processes() {
+        let sampler = MockSampler::default();
+        let mut scripts = vec![script(1, 100), script(2, 200)];
+        let mut previous = HashMap::new();
+        let start = Instant::now();
+        sampler.set(100, Duration::ZERO, 0);
+        sampler.set(200, Duration::ZERO, 0);
+        sample_scripts(&sampler, &scripts, &HashMap::new(), &mut previous, start);
+
+        sampler.exit(200);
+        let stats = sample_scripts(&sampler, &scripts, &HashMap::new(), &mut previous, start);
+        let ids: Vec<u32> = stats.iter().map(|s| s.id).collect();
+        assert_eq!(ids, vec![1]);
+
+        // Once the script is gone its last sample is forgotten too.
+        scripts.pop();
+        sample_scripts(&sampler, &scripts, &HashMap::new(), &mut previous, start);
+        assert!(!previous.contains_key(&2));
+    }
+}
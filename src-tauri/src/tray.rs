@@ -0,0 +1,222 @@
+use std::sync::Mutex;
+
+use tauri::{
+    menu::{Menu, MenuItem, PredefinedMenuItem, Submenu},
+    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
+    Manager, WebviewWindow, WindowEvent, Wry,
+};
+use tauri_plugin_notification::NotificationExt;
+use tauri_plugin_opener::OpenerExt;
+
+use crate::{
+    scripts::{stop_script, ExitReason, ScriptRecord},
+    settings::SettingsStore,
+    webhooks::{EventKind, ScriptEvent},
+    LauncherVariables,
+};
+
+const TRAY_ID: &str = "main";
+
+fn running_scripts(app: &tauri::AppHandle) -> Vec<ScriptRecord> {
+    let Some(launcher) = app.try_state::<Mutex<LauncherVariables>>() else {
+        return Vec::new();
+    };
+    let launcher = launcher.lock().unwrap();
+    let records = launcher.registry.lock().unwrap().records();
+    records
+}
+
+fn build_menu(app: &tauri::AppHandle, records: &[ScriptRecord]) -> tauri::Result<Menu<Wry>> {
+    let menu = Menu::new(app)?;
+
+    if records.is_empty() {
+        menu.append(&MenuItem::with_id(
+            app,
+            "none",
+            "No scripts running",
+            false,
+            None::<&str>,
+        )?)?;
+    }
+
+    for record in records {
+        let title = match &record.client {
+            Some(client) => format!(
+                "{} ({}) on {}",
+                record.script_id,
+                record.id,
+                client.display_name()
+            ),
+            None => format!("{} ({})", record.script_id, record.id),
+        };
+
+        let show_log = MenuItem::with_id(
+            app,
+            format!("log:{}", record.id),
+            "Show log",
+            true,
+            None::<&str>,
+        )?;
+        let stop = MenuItem::with_id(
+            app,
+            format!("stop:{}", record.id),
+            "Stop",
+            true,
+            None::<&str>,
+        )?;
+        menu.append(&Submenu::with_items(app, title, true, &[&show_log, &stop])?)?;
+    }
+
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    menu.append(&MenuItem::with_id(
+        app,
+        "show",
+        "Show launcher",
+        true,
+        None::<&str>,
+    )?)?;
+    menu.append(&MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?)?;
+    Ok(menu)
+}
+
+fn tooltip(records: &[ScriptRecord]) -> String {
+    match records.len() {
+        1 => "WaspScripts - 1 script running".to_string(),
+        n => format!("WaspScripts - {} scripts running", n),
+    }
+}
+
+fn show_main_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+fn handle_menu_event(app: &tauri::AppHandle, id: &str) {
+    match id.split_once(':') {
+        Some(("stop", id)) => {
+            if let Ok(id) = id.parse() {
+                if let Err(e) = stop_script(app, id, ExitReason::Killed) {
+                    eprintln!("{}", e);
+                }
+            }
+        }
+        Some(("log", id)) => {
+            let record = id
+                .parse::<u32>()
+                .ok()
+                .and_then(|id| running_scripts(app).into_iter().find(|r| r.id == id));
+            if let Some(record) = record {
+                let path = record.log_path.to_string_lossy().to_string();
+                if let Err(e) = app.opener().open_path(path, None::<&str>) {
+                    eprintln!("Failed to open log: {}", e);
+                }
+            }
+        }
+        _ => match id {
+            "show" => show_main_window(app),
+            // Scripts keep running and are re-adopted on the next start.
+            "quit" => app.exit(0),
+            _ => {}
+        },
+    }
+}
+
+fn build_tray(app: &tauri::AppHandle) -> tauri::Result<()> {
+    let records = running_scripts(app);
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip(tooltip(&records))
+        .menu(&build_menu(app, &records)?)
+        .on_menu_event(|app, event| handle_menu_event(app, event.id().as_ref()))
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                show_main_window(tray.app_handle());
+            }
+        });
+
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+    Ok(())
+}
+
+/// Creates the tray icon and keeps the launcher running in it when the main window
+/// is closed. Without a tray, closing the window quits as usual.
+pub fn create_tray(app: &tauri::AppHandle, window: &WebviewWindow) {
+    if let Err(e) = build_tray(app) {
+        eprintln!("Failed to create tray icon: {}", e);
+        return;
+    }
+
+    let hidden = window.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::CloseRequested { api, .. } = event {
+            api.prevent_close();
+            let _ = hidden.hide();
+        }
+    });
+}
+
+/// Rebuilds the tray menu from the running scripts.
+pub fn refresh_tray(app: &tauri::AppHandle) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+
+    let records = running_scripts(app);
+    match build_menu(app, &records) {
+        Ok(menu) => {
+            let _ = tray.set_menu(Some(menu));
+            let _ = tray.set_tooltip(Some(tooltip(&records)));
+        }
+        Err(e) => eprintln!("Failed to update tray menu: {}", e),
+    }
+}
+
+/// Shows a desktop notification for a script that stopped, restarted, went quiet or
+/// hit a trigger. Headless runs only print to the terminal.
+pub fn notify_desktop(app: &tauri::AppHandle, event: &ScriptEvent) {
+    let headless = app
+        .try_state::<Mutex<LauncherVariables>>()
+        .is_none_or(|launcher| launcher.lock().unwrap().headless);
+    let enabled = app
+        .try_state::<SettingsStore>()
+        .map(|settings| settings.get().notifications)
+        .unwrap_or(false);
+    if headless || !enabled {
+        return;
+    }
+
+    let title = match event.event {
+        EventKind::Crashed => "Script crashed",
+        EventKind::Finished => "Script finished",
+        EventKind::Triggered => "Script trigger",
+        EventKind::Restarted => "Script restarted",
+        EventKind::Inactive => "Script inactive",
+        EventKind::Started => return,
+    };
+
+    let name = if event.script.is_empty() {
+        format!("Script {}", event.id)
+    } else {
+        event.script.clone()
+    };
+
+    if let Err(e) = app
+        .notification()
+        .builder()
+        .title(title)
+        .body(format!("{}: {}", name, event.details))
+        .show()
+    {
+        eprintln!("Failed to show notification: {}", e);
+    }
+}
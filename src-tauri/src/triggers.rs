@@ -0,0 +1,267 @@
+use std::{fs, sync::Mutex};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{Emitter, Manager};
+
+use crate::{
+    client::ClientProvider,
+    scripts::{restart_script, stop_script, unix_now, ExitReason},
+    tray::notify_desktop,
+    webhooks::{notify, EventKind, ScriptEvent},
+    LauncherVariables,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TriggerAction {
+    /// Desktop notification and webhooks.
+    Notify,
+    Stop,
+    /// Restarts with a backoff and gives up after a few tries, see `restart_script`.
+    Restart,
+    /// Saves the client window to Simba's `Screenshots` directory.
+    Screenshot,
+    /// Adds `tag` to the run, see `ScriptRecord::tags`.
+    Tag {
+        tag: String,
+    },
+}
+
+impl TriggerAction {
+    /// Stopping or restarting ends the run, so these go after every other action.
+    fn ends_run(&self) -> bool {
+        matches!(self, TriggerAction::Stop | TriggerAction::Restart)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TriggerRule {
+    pub name: String,
+    /// Regex matched against every line a script prints.
+    pub pattern: String,
+    /// Script IDs the rule applies to, every script when empty.
+    pub scripts: Vec<String>,
+    pub actions: Vec<TriggerAction>,
+}
+
+pub struct Trigger {
+    pub rule: TriggerRule,
+    regex: Regex,
+}
+
+impl Trigger {
+    pub fn new(rule: TriggerRule) -> Result<Self, String> {
+        let regex = Regex::new(&rule.pattern)
+            .map_err(|e| format!("Invalid pattern for trigger {:?}: {}", rule.name, e))?;
+        Ok(Self { rule, regex })
+    }
+
+    pub fn is_match(&self, line: &str) -> bool {
+        self.regex.is_match(line)
+    }
+}
+
+/// Compiles the rules that apply to `script_id`.
+pub fn compile(rules: &[TriggerRule], script_id: &str) -> Result<Vec<Trigger>, String> {
+    rules
+        .iter()
+        .filter(|rule| rule.scripts.is_empty() || rule.scripts.iter().any(|s| s == script_id))
+        .cloned()
+        .map(Trigger::new)
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TriggerMatch {
+    pub line: usize,
+    pub text: String,
+    pub rule: String,
+    pub actions: Vec<TriggerAction>,
+}
+
+/// Runs `rules` against `lines` without acting on anything.
+pub fn dry_run(
+    rules: &[TriggerRule],
+    script_id: &str,
+    lines: &[String],
+) -> Result<Vec<TriggerMatch>, String> {
+    let triggers = compile(rules, script_id)?;
+    let mut matches = Vec::new();
+
+    for (index, text) in lines.iter().enumerate() {
+        for trigger in triggers.iter().filter(|t| t.is_match(text)) {
+            matches.push(TriggerMatch {
+                line: index + 1,
+                text: text.clone(),
+                rule: trigger.rule.name.clone(),
+                actions: trigger.rule.actions.clone(),
+            });
+        }
+    }
+
+    Ok(matches)
+}
+
+fn screenshot(app: &tauri::AppHandle, id: u32) -> Result<String, String> {
+    let (client, simba) = {
+        let launcher = app.state::<Mutex<LauncherVariables>>();
+        let launcher = launcher.lock().unwrap();
+        let client = launcher.bindings.lock().unwrap().get(&id).cloned();
+        (client, launcher.simba.clone())
+    };
+
+    let client = client.ok_or_else(|| format!("Script {} has no client", id))?;
+    let capture = app
+        .state::<Box<dyn ClientProvider>>()
+        .capture(&client)
+        .ok_or_else(|| format!("Failed to capture the client of script {}", id))?;
+
+    let dir = simba.join("Screenshots");
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!("{}-{}.bmp", id, unix_now()));
+    fs::write(&path, capture.to_bmp()).map_err(|e| e.to_string())?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+fn tag(app: &tauri::AppHandle, id: u32, tag: &str) -> Result<(), String> {
+    let launcher = app.state::<Mutex<LauncherVariables>>();
+    let launcher = launcher.lock().unwrap();
+    let mut registry = launcher.registry.lock().unwrap();
+    if registry.tag(id, tag) {
+        Ok(())
+    } else {
+        Err(format!("No active script found for ID {}", id))
+    }
+}
+
+/// `actions` in the order `fire` carries them out, see `TriggerAction::ends_run`.
+fn in_order(actions: &[TriggerAction]) -> Vec<&TriggerAction> {
+    let (last, mut first): (Vec<_>, Vec<_>) = actions.iter().partition(|a| a.ends_run());
+    first.extend(last);
+    first
+}
+
+/// Carries out the actions of `trigger`, which matched `line` of script `id`.
+pub fn fire(app: &tauri::AppHandle, id: u32, trigger: &Trigger, line: &str) {
+    let rule = &trigger.rule;
+    println!("Trigger {} matched in script {}: {}", rule.name, id, line);
+    let _ = app.emit(
+        "script-trigger",
+        json!({ "id": id, "rule": rule.name, "line": line, "actions": rule.actions }),
+    );
+
+    for action in in_order(&rule.actions) {
+        let result = match action {
+            TriggerAction::Notify => {
+                if let Some(mut event) = ScriptEvent::running(app, id, EventKind::Triggered) {
+                    event.details = format!("{}: {}", rule.name, line);
+                    notify_desktop(app, &event);
+                    notify(app, event);
+                }
+                Ok(())
+            }
+            TriggerAction::Stop => stop_script(app, id, ExitReason::Triggered).map(|_| ()),
+            TriggerAction::Restart => {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = restart_script(&app, id, ExitReason::Triggered).await {
+                        eprintln!("Failed to restart script {}: {}", id, e);
+                    }
+                });
+                Ok(())
+            }
+            TriggerAction::Screenshot => screenshot(app, id).map(|path| {
+                let _ = app.emit("script-screenshot", json!({ "id": id, "path": path }));
+            }),
+            TriggerAction::Tag { tag: name } => tag(app, id, name).map(|_| {
+                let _ = app.emit("script-tagged", json!({ "id": id, "tag": name }));
+            }),
+        };
+
+        if let Err(e) = result {
+            eprintln!("Trigger {} failed: {}", rule.name, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(name: &str, pattern: &str, scripts: &[&str]) -> TriggerRule {
+        TriggerRule {
+            name: name.to_string(),
+            pattern: pattern.to_string(),
+            scripts: scripts.iter().map(|s| s.to_string()).collect(),
+            actions: vec![TriggerAction::Notify],
+        }
+    }
+
+    #[test]
+    fn compiles_rules_for_the_script_only() {
+        let rules = [
+            rule("everywhere", "a", &[]),
+            rule("mine", "b", &["miner"]),
+            rule("theirs", "c", &["fisher"]),
+        ];
+        let names: Vec<String> = compile(&rules, "miner")
+            .unwrap()
+            .into_iter()
+            .map(|t| t.rule.name)
+            .collect();
+        assert_eq!(names, vec!["everywhere", "mine"]);
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        let rules = [rule("broken", "(unclosed", &[])];
+        let error = compile(&rules, "miner").err().unwrap();
+        assert!(error.contains("\"broken\""), "{}", error);
+
+        // Rules for other scripts are not compiled.
+        let rules = [rule("broken", "(unclosed", &["fisher"])];
+        assert!(compile(&rules, "miner").unwrap().is_empty());
+    }
+
+    #[test]
+    fn dry_run_reports_every_match() {
+        let rules = [rule("died", "Oh dear", &[]), rule("any", "dear", &[])];
+        let lines = vec![
+            "Walking to bank".to_string(),
+            "Oh dear, you are dead!".to_string(),
+        ];
+        let matches = dry_run(&rules, "miner", &lines).unwrap();
+
+        let found: Vec<(usize, &str)> = matches.iter().map(|m| (m.line, m.rule.as_str())).collect();
+        assert_eq!(found, vec![(2, "died"), (2, "any")]);
+        assert_eq!(matches[0].text, lines[1]);
+        assert_eq!(matches[0].actions, vec![TriggerAction::Notify]);
+    }
+
+    #[test]
+    fn ends_the_run_after_every_other_action() {
+        let tag = TriggerAction::Tag {
+            tag: "died".to_string(),
+        };
+        let actions = vec![
+            TriggerAction::Stop,
+            TriggerAction::Screenshot,
+            TriggerAction::Restart,
+            tag.clone(),
+            TriggerAction::Notify,
+        ];
+        assert_eq!(
+            in_order(&actions),
+            vec![
+                &TriggerAction::Screenshot,
+                &tag,
+                &TriggerAction::Notify,
+                &TriggerAction::Stop,
+                &TriggerAction::Restart,
+            ]
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{Emitter, Manager};
+
+use crate::{
+    output::ScriptOutput,
+    scripts::{restart_script, stop_script, ExitReason, SharedProcess},
+    tray::notify_desktop,
+    webhooks::{notify, EventKind, ScriptEvent},
+    LauncherVariables,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchdogAction {
+    /// Only emit `script-inactive` and notify.
+    #[default]
+    Alert,
+    Kill,
+    Restart,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchdogConfig {
+    /// Seconds without output before the script counts as stuck, 0 disables the watchdog.
+    pub timeout: u64,
+    /// Regex a line has to match to count as activity, any line does when unset.
+    pub heartbeat: Option<String>,
+    pub action: WatchdogAction,
+}
+
+/// Whether `process` is still the run of script `id`. A restart keeps the ID but
+/// starts a new process, which gets a watchdog of its own.
+fn is_current(scripts: &HashMap<u32, SharedProcess>, id: u32, process: &SharedProcess) -> bool {
+    scripts
+        .get(&id)
+        .is_some_and(|current| Arc::ptr_eq(current, process))
+}
+
+fn is_running(app: &tauri::AppHandle, id: u32, process: &SharedProcess) -> bool {
+    let launcher = app.state::<Mutex<LauncherVariables>>();
+    let launcher = launcher.lock().unwrap();
+    let running = is_current(&launcher.scripts.lock().unwrap(), id, process);
+    running
+}
+
+/// Watches the output of `process`, the run of script `id`, until it exits, acting
+/// once it has been quiet for longer than `config.timeout`.
+pub fn start_watchdog(
+    app: tauri::AppHandle,
+    id: u32,
+    process: SharedProcess,
+    output: Arc<ScriptOutput>,
+    config: WatchdogConfig,
+) {
+    if config.timeout == 0 {
+        return;
+    }
+
+    let timeout = Duration::from_secs(config.timeout);
+    let interval = (timeout / 10).clamp(Duration::from_millis(500), Duration::from_secs(5));
+
+    thread::spawn(move || {
+        let mut alerted = false;
+
+        loop {
+            thread::sleep(interval);
+            if !is_running(&app, id, &process) {
+                return;
+            }
+
+            let idle = output.idle();
+            if idle < timeout {
+                alerted = false;
+                continue;
+            }
+            if alerted {
+                continue;
+            }
+            alerted = true;
+
+            println!("Script {} printed nothing for {}s", id, idle.as_secs());
+            let _ = app.emit(
+                "script-inactive",
+                json!({ "id": id, "idle": idle.as_secs(), "action": config.action }),
+            );
+            if let Some(mut event) = ScriptEvent::running(&app, id, EventKind::Inactive) {
+                event.details = format!("no output for {}s", idle.as_secs());
+                notify_desktop(&app, &event);
+                notify(&app, event);
+            }
+
+            match config.action {
+                WatchdogAction::Alert => {}
+                WatchdogAction::Kill => {
+                    let _ = stop_script(&app, id, ExitReason::Inactive);
+                    return;
+                }
+                WatchdogAction::Restart => {
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = restart_script(&app, id, ExitReason::Inactive).await {
+                            eprintln!("Failed to restart script {}: {}", id, e);
+                        }
+                    });
+                    return;
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scripts::ScriptProcess;
+
+    #[test]
+    fn stops_watching_once_the_run_is_replaced() {
+        let first: SharedProcess = Arc::new(Mutex::new(Some(ScriptProcess::Adopted(1))));
+        let second: SharedProcess = Arc::new(Mutex::new(Some(ScriptProcess::Adopted(2))));
+        let mut scripts = HashMap::from([(7, first.clone())]);
+        assert!(is_current(&scripts, 7, &first));
+
+        // Restarted under the same ID.
+        scripts.insert(7, second.clone());
+        assert!(!is_current(&scripts, 7, &first));
+        assert!(is_current(&scripts, 7, &second));
+
+        scripts.clear();
+        assert!(!is_current(&scripts, 7, &second));
+    }
+}
@@ -0,0 +1,487 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::Manager;
+use tauri_plugin_http::reqwest::{header::CONTENT_TYPE, Client};
+
+use crate::{
+    scripts::{unix_now, ExitReason, ScriptExit, ScriptRecord},
+    settings::SettingsStore,
+    LauncherVariables,
+};
+
+const DEFAULT_TEMPLATE: &str = "[{event}] {script} ({id}) {details}";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    Started,
+    Finished,
+    Crashed,
+    Restarted,
+    /// The script stopped printing, see `watchdog`.
+    Inactive,
+    /// A line of output matched a trigger with the notify action, see `triggers`.
+    Triggered,
+}
+
+impl EventKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            EventKind::Started => "started",
+            EventKind::Finished => "finished",
+            EventKind::Crashed => "crashed",
+            EventKind::Restarted => "restarted",
+            EventKind::Inactive => "inactive",
+            EventKind::Triggered => "triggered",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookFormat {
+    /// `{"content": message}`, accepted by Discord and Slack compatible endpoints.
+    Discord,
+    /// Every event field plus the rendered message.
+    #[default]
+    Json,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Webhook {
+    pub name: String,
+    pub url: String,
+    pub format: WebhookFormat,
+    /// Events to post, all of them when empty.
+    pub events: Vec<EventKind>,
+    /// Message template. `{event}`, `{id}`, `{script}`, `{pid}`, `{code}`, `{reason}`,
+    /// `{details}`, `{client}` and `{time}` are replaced with the event's values.
+    pub template: Option<String>,
+    /// Minimum seconds between two posts. Only the latest event in between is posted,
+    /// once the interval is over, along with how many it replaced. Crashes are always
+    /// posted right away.
+    pub min_interval: u64,
+}
+
+impl Default for Webhook {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            url: String::new(),
+            format: WebhookFormat::default(),
+            events: Vec::new(),
+            template: None,
+            min_interval: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScriptEvent {
+    pub event: EventKind,
+    pub id: u32,
+    pub script: String,
+    pub pid: Option<u32>,
+    pub code: Option<i32>,
+    pub reason: Option<ExitReason>,
+    pub details: String,
+    pub client: Option<String>,
+    pub time: u64,
+}
+
+impl ScriptEvent {
+    pub fn started(record: &ScriptRecord) -> Self {
+        Self {
+            event: EventKind::Started,
+            id: record.id,
+            script: record.script_id.clone(),
+            pid: Some(record.pid),
+            code: None,
+            reason: None,
+            details: String::new(),
+            client: record.client.as_ref().map(|c| c.display_name()),
+            time: unix_now(),
+        }
+    }
+
+    /// Event about script `id` while it is still running.
+    pub fn running(app: &tauri::AppHandle, id: u32, event: EventKind) -> Option<Self> {
+        let launcher = app.try_state::<Mutex<LauncherVariables>>()?;
+        let record = {
+            let guard = launcher.lock().unwrap();
+            let records = guard.registry.lock().unwrap().records();
+            records.into_iter().find(|record| record.id == id)?
+        };
+
+        Some(Self {
+            event,
+            ..Self::started(&record)
+        })
+    }
+
+    /// Scripts that exit on their own with code 0 or are stopped on purpose finished,
+    /// everything else crashed.
+    pub fn exited(id: u32, record: Option<&ScriptRecord>, exit: ScriptExit) -> Self {
+        let event = match (exit.reason, exit.code) {
+            (ExitReason::Exited, Some(0)) | (ExitReason::Killed, _) => EventKind::Finished,
+            _ => EventKind::Crashed,
+        };
+
+        Self {
+            event,
+            id,
+            script: record.map(|r| r.script_id.clone()).unwrap_or_default(),
+            pid: record.map(|r| r.pid),
+            code: exit.code,
+            reason: Some(exit.reason),
+            details: exit.to_string(),
+            client: record.and_then(|r| r.client.as_ref().map(|c| c.display_name())),
+            time: unix_now(),
+        }
+    }
+
+    fn render(&self, template: &str) -> String {
+        let reason = self
+            .reason
+            .and_then(|r| serde_json::to_value(r).ok())
+            .and_then(|r| r.as_str().map(str::to_string))
+            .unwrap_or_default();
+
+        template
+            .replace("{event}", self.event.as_str())
+            .replace("{id}", &self.id.to_string())
+            .replace("{script}", &self.script)
+            .replace(
+                "{pid}",
+                &self.pid.map(|p| p.to_string()).unwrap_or_default(),
+            )
+            .replace(
+                "{code}",
+                &self.code.map(|c| c.to_string()).unwrap_or_default(),
+            )
+            .replace("{reason}", &reason)
+            .replace("{details}", &self.details)
+            .replace("{client}", self.client.as_deref().unwrap_or(""))
+            .replace("{time}", &self.time.to_string())
+    }
+}
+
+fn payload(webhook: &Webhook, event: &ScriptEvent, suppressed: u32) -> Value {
+    let mut message = event.render(webhook.template.as_deref().unwrap_or(DEFAULT_TEMPLATE));
+    if suppressed > 0 {
+        message.push_str(&format!(" ({} earlier events suppressed)", suppressed));
+    }
+
+    match webhook.format {
+        WebhookFormat::Discord => json!({ "content": message }),
+        WebhookFormat::Json => {
+            let mut value = serde_json::to_value(event).unwrap_or_else(|_| json!({}));
+            value["message"] = Value::String(message);
+            value["suppressed"] = Value::from(suppressed);
+            value
+        }
+    }
+}
+
+struct RateState {
+    last: Instant,
+    suppressed: u32,
+    /// Latest event held back by the rate limit, posted by the scheduled flush.
+    pending: Option<ScriptEvent>,
+}
+
+enum Admission {
+    /// Post now, along with how many events were replaced since the last post.
+    Send(u32),
+    /// Held back, flush the webhook after the delay.
+    Later(Duration),
+    /// Held back, a flush is already scheduled.
+    Queued,
+}
+
+/// Posts script events to the configured webhooks, at most once per `min_interval`
+/// for each URL.
+#[derive(Default)]
+pub struct WebhookDispatcher {
+    rates: Mutex<HashMap<String, RateState>>,
+}
+
+impl WebhookDispatcher {
+    fn admit(&self, webhook: &Webhook, event: &ScriptEvent, now: Instant) -> Admission {
+        if event.event == EventKind::Crashed {
+            return Admission::Send(0);
+        }
+
+        let mut rates = self.rates.lock().unwrap();
+        let Some(state) = rates.get_mut(&webhook.url) else {
+            rates.insert(
+                webhook.url.clone(),
+                RateState {
+                    last: now,
+                    suppressed: 0,
+                    pending: None,
+                },
+            );
+            return Admission::Send(0);
+        };
+
+        let interval = Duration::from_secs(webhook.min_interval);
+        let elapsed = now.duration_since(state.last);
+        let scheduled = state.pending.is_some();
+        if !scheduled && elapsed >= interval {
+            state.last = now;
+            return Admission::Send(std::mem::take(&mut state.suppressed));
+        }
+
+        if state.pending.replace(event.clone()).is_some() {
+            state.suppressed += 1;
+        }
+        if scheduled {
+            Admission::Queued
+        } else {
+            Admission::Later(interval - elapsed)
+        }
+    }
+
+    /// Takes the event held back for `webhook` and how many it replaced.
+    fn take_pending(&self, webhook: &Webhook, now: Instant) -> Option<(ScriptEvent, u32)> {
+        let mut rates = self.rates.lock().unwrap();
+        let state = rates.get_mut(&webhook.url)?;
+        let event = state.pending.take()?;
+        state.last = now;
+        Some((event, std::mem::take(&mut state.suppressed)))
+    }
+}
+
+async fn post(webhook: &Webhook, body: &Value) -> Result<(), String> {
+    Client::new()
+        .post(&webhook.url)
+        .header(CONTENT_TYPE, "application/json")
+        .body(body.to_string())
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map(|_| ())
+        .map_err(|e| format!("Webhook {} failed: {}", webhook.name, e))
+}
+
+fn send(webhook: Webhook, body: Value) {
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = post(&webhook, &body).await {
+            eprintln!("{}", e);
+        }
+    });
+}
+
+/// Posts the event held back for `webhook` once its interval is over.
+fn flush_later(app: &tauri::AppHandle, webhook: Webhook, delay: Duration) {
+    let app = app.clone();
+    thread::spawn(move || {
+        thread::sleep(delay);
+        let Some(dispatcher) = app.try_state::<WebhookDispatcher>() else {
+            return;
+        };
+        if let Some((event, suppressed)) = dispatcher.take_pending(&webhook, Instant::now()) {
+            let body = payload(&webhook, &event, suppressed);
+            send(webhook, body);
+        }
+    });
+}
+
+/// Sends `event` to every webhook subscribed to it in the background.
+pub fn notify(app: &tauri::AppHandle, event: ScriptEvent) {
+    let (Some(settings), Some(dispatcher)) = (
+        app.try_state::<SettingsStore>(),
+        app.try_state::<WebhookDispatcher>(),
+    ) else {
+        return;
+    };
+
+    for webhook in settings.get().webhooks {
+        if webhook.url.is_empty()
+            || !(webhook.events.is_empty() || webhook.events.contains(&event.event))
+        {
+            continue;
+        }
+
+        match dispatcher.admit(&webhook, &event, Instant::now()) {
+            Admission::Send(suppressed) => {
+                let body = payload(&webhook, &event, suppressed);
+                send(webhook, body);
+            }
+            Admission::Later(delay) => flush_later(app, webhook, delay),
+            Admission::Queued => {}
+        }
+    }
+}
+
+/// Posts a sample event to `webhook` right away, ignoring its filters and rate limit.
+pub async fn test_webhook(webhook: &Webhook) -> Result<(), String> {
+    let event = ScriptEvent {
+        event: EventKind::Finished,
+        id: 0,
+        script: "test".to_string(),
+        pid: None,
+        code: Some(0),
+        reason: Some(ExitReason::Exited),
+        details: "exit code: 0".to_string(),
+        client: None,
+        time: unix_now(),
+    };
+
+    post(webhook, &payload(webhook, &event, 0)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::mpsc,
+    };
+
+    use super::*;
+
+    fn webhook(url: &str, min_interval: u64) -> Webhook {
+        Webhook {
+            name: "hook".to_string(),
+            url: url.to_string(),
+            min_interval,
+            ..Default::default()
+        }
+    }
+
+    fn event(kind: EventKind) -> ScriptEvent {
+        ScriptEvent {
+            event: kind,
+            id: 1,
+            script: "script".to_string(),
+            pid: Some(42),
+            code: None,
+            reason: None,
+            details: String::new(),
+            client: None,
+            time: 0,
+        }
+    }
+
+    /// Accepts one request, answers it with `status` and hands back the body.
+    fn stand_in(status: &'static str) -> (String, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(&stream);
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim_end().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+
+            let response = format!("HTTP/1.1 {}\r\nContent-Length: 0\r\n\r\n", status);
+            (&stream).write_all(response.as_bytes()).unwrap();
+            tx.send(String::from_utf8(body).unwrap()).unwrap();
+        });
+
+        (url, rx)
+    }
+
+    #[test]
+    fn posts_the_rendered_event() {
+        let (url, body) = stand_in("204 No Content");
+        let mut hook = webhook(&url, 0);
+        hook.format = WebhookFormat::Discord;
+
+        let event = event(EventKind::Started);
+        tauri::async_runtime::block_on(post(&hook, &payload(&hook, &event, 2))).unwrap();
+
+        let body: Value = serde_json::from_str(&body.recv().unwrap()).unwrap();
+        assert_eq!(
+            body["content"],
+            "[started] script (1)  (2 earlier events suppressed)"
+        );
+    }
+
+    #[test]
+    fn reports_error_responses() {
+        let (url, _body) = stand_in("500 Internal Server Error");
+        let hook = webhook(&url, 0);
+
+        let event = event(EventKind::Finished);
+        let err = tauri::async_runtime::block_on(post(&hook, &payload(&hook, &event, 0)));
+        assert!(err.unwrap_err().starts_with("Webhook hook failed"));
+    }
+
+    #[test]
+    fn holds_back_the_latest_event_until_the_interval_ends() {
+        let dispatcher = WebhookDispatcher::default();
+        let hook = webhook("http://localhost/a", 10);
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        assert!(matches!(
+            dispatcher.admit(&hook, &event(EventKind::Started), at(0)),
+            Admission::Send(0)
+        ));
+        assert!(matches!(
+            dispatcher.admit(&hook, &event(EventKind::Inactive), at(4)),
+            Admission::Later(delay) if delay == Duration::from_secs(6)
+        ));
+        assert!(matches!(
+            dispatcher.admit(&hook, &event(EventKind::Finished), at(5)),
+            Admission::Queued
+        ));
+
+        let (pending, suppressed) = dispatcher.take_pending(&hook, at(10)).unwrap();
+        assert_eq!(pending.event, EventKind::Finished);
+        assert_eq!(suppressed, 1);
+        assert!(dispatcher.take_pending(&hook, at(10)).is_none());
+
+        assert!(matches!(
+            dispatcher.admit(&hook, &event(EventKind::Started), at(21)),
+            Admission::Send(0)
+        ));
+    }
+
+    #[test]
+    fn crashes_and_other_urls_are_not_held_back() {
+        let dispatcher = WebhookDispatcher::default();
+        let first = webhook("http://localhost/a", 60);
+        let second = webhook("http://localhost/b", 60);
+        let now = Instant::now();
+
+        assert!(matches!(
+            dispatcher.admit(&first, &event(EventKind::Started), now),
+            Admission::Send(0)
+        ));
+        // Same name, different endpoint.
+        assert!(matches!(
+            dispatcher.admit(&second, &event(EventKind::Started), now),
+            Admission::Send(0)
+        ));
+        assert!(matches!(
+            dispatcher.admit(&first, &event(EventKind::Crashed), now),
+            Admission::Send(0)
+        ));
+    }
+}
@@ -95,33 +95,37 @@ class ChannelManager {
 	processes = $state<number[]>([])
 	channels = $state<Record<number, ChannelEntry>>({})
 
-	async createChannel(name: string): Promise<Channel<string>> {
+	// Scripts are keyed by the ID the launcher gives them, which is only known once
+	// `run_script` returns. Output that arrives before `attach` is kept until then.
+	createChannel(name: string): { channel: Channel<string>; attach: (id: number) => void } {
 		const channel = new Channel<string>()
-		const id = channel.id
-
-		this._logsBuffer[id] = []
-		this.channels[id] = { name, version: 0, stopped: false, start: Date.now(), finish: 0 }
-		this.processes.push(id)
+		const buffer: LogSegment[] = []
+		const start = Date.now()
+		let scriptId: number | null = null
 
 		channel.onmessage = (msg: string) => {
-			const entry = this.channels[id]
-			if (!entry || entry.stopped) {
+			const entry = scriptId === null ? null : this.channels[scriptId]
+			if (scriptId !== null && (!entry || entry.stopped)) {
 				return
 			}
 
-			const buffer = this._logsBuffer[id]
-			const parsed = parseLogMessage(msg)
-
-			buffer.push(...parsed)
+			buffer.push(...parseLogMessage(msg))
 
 			while (buffer.length > MAX_LOGS) {
 				buffer.shift()
 			}
 
-			entry.version++
+			if (entry) entry.version++
+		}
+
+		const attach = (id: number) => {
+			scriptId = id
+			this._logsBuffer[id] = buffer
+			this.channels[id] = { name, version: 0, stopped: false, start, finish: 0 }
+			this.processes.push(id)
 		}
 
-		return channel
+		return { channel, attach }
 	}
 
 	stopChannel(id: number) {